@@ -0,0 +1,2 @@
+#![no_main]
+libfuzzer_sys::fuzz_target!(|data: &[u8]| aoc20::fuzz::run_day(2020, 10, data));
@@ -1,13 +1,5 @@
 set -e
 RUSTFLAGS=
 
-DAY=$1
-QUESTION=$2
-INPUTFILE=./inputs/$DAY.txt
-
-mkdir -p ./inputs/
-if [ ! -f $INPUTFILE ]; then
-    curl "https://adventofcode.com/2020/day/$DAY/input" -H "cookie: $(cat cookie.txt)" --compressed > $INPUTFILE
-fi
-
+# missing inputs are automatically downloaded (using cookie.txt) by the binary
 cargo run -- $@
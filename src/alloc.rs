@@ -0,0 +1,162 @@
+//! Heap allocation accounting, enabled with the `alloc-stats` feature.
+//!
+//! When enabled, a counting global allocator is installed, and every run
+//! reports how many allocations it made, how many bytes it allocated in total,
+//! and the peak amount of heap memory it had in use at once.
+//!
+//! The counters are process-wide, so numbers are only meaningful when a single
+//! solution is running at a time.
+
+/// Allocations made while running a single part of a solution.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Number of allocations (including reallocations).
+    pub allocs: usize,
+    /// Total number of bytes allocated.
+    pub bytes: usize,
+    /// Peak heap usage above what was in use when the run started.
+    pub peak: usize,
+}
+
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+    pub static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+    pub static BYTES: AtomicUsize = AtomicUsize::new(0);
+    pub static CURRENT: AtomicUsize = AtomicUsize::new(0);
+    pub static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+    pub struct Counting;
+
+    impl Counting {
+        fn grow(&self, size: usize) {
+            ALLOCS.fetch_add(1, Relaxed);
+            BYTES.fetch_add(size, Relaxed);
+            let current = CURRENT.fetch_add(size, Relaxed) + size;
+            PEAK.fetch_max(current, Relaxed);
+        }
+    }
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                self.grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                self.grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            CURRENT.fetch_sub(layout.size(), Relaxed);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                CURRENT.fetch_sub(layout.size(), Relaxed);
+                self.grow(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: Counting = Counting;
+}
+
+/// Run a closure, recording the allocations it makes.
+///
+/// Returns `None` in place of stats if the `alloc-stats` feature is disabled.
+#[cfg(feature = "alloc-stats")]
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+    use counting::*;
+    use std::sync::atomic::Ordering::Relaxed;
+
+    let start_current = CURRENT.load(Relaxed);
+    PEAK.store(start_current, Relaxed);
+    let start_allocs = ALLOCS.load(Relaxed);
+    let start_bytes = BYTES.load(Relaxed);
+
+    let ret = f();
+
+    let stats = AllocStats {
+        allocs: ALLOCS.load(Relaxed) - start_allocs,
+        bytes: BYTES.load(Relaxed) - start_bytes,
+        peak: PEAK.load(Relaxed).saturating_sub(start_current),
+    };
+    (ret, Some(stats))
+}
+
+/// Run a closure, recording the allocations it makes.
+///
+/// Returns `None` in place of stats if the `alloc-stats` feature is disabled.
+#[cfg(not(feature = "alloc-stats"))]
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+    (f(), None)
+}
+
+/// Format a number of bytes using the most appropriate binary unit.
+pub fn fmt_bytes(bytes: usize) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+
+    let mut n = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if n < 1024.0 {
+            break;
+        }
+        n /= 1024.0;
+        unit = next;
+    }
+    format!("{:.2}{}", n, unit)
+}
+
+impl core::fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} allocs, {} allocated, {} peak",
+            self.allocs,
+            fmt_bytes(self.bytes),
+            fmt_bytes(self.peak)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes() {
+        assert_eq!(fmt_bytes(512), "512B");
+        assert_eq!(fmt_bytes(1536), "1.50KiB");
+        assert_eq!(fmt_bytes(3 * 1024 * 1024), "3.00MiB");
+        assert_eq!(fmt_bytes(5 * 1024 * 1024 * 1024), "5.00GiB");
+    }
+
+    #[cfg(feature = "alloc-stats")]
+    #[test]
+    fn counts_allocations() {
+        let (v, stats) = measure(|| vec![0u8; 4096]);
+        let stats = stats.unwrap();
+        assert!(stats.allocs >= 1);
+        assert!(stats.bytes >= 4096);
+        drop(v);
+    }
+}
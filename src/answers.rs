@@ -0,0 +1,260 @@
+//! Recorded answers, used to catch regressions when refactoring shared code.
+//!
+//! Answers are stored in a small subset of TOML, with one table per day. Days
+//! from years other than 2020 are prefixed with their year:
+//!
+//! ```toml
+//! [day1]
+//! part1 = 514579
+//! part2 = 241861950
+//!
+//! [day21]
+//! part1 = 5
+//! part2 = "mxmxvkd,sqjhc,fvjkl"
+//!
+//! [2019.day1]
+//! part1 = 3394032
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::runner::Run;
+use crate::solution::{Answer, DEFAULT_YEAR};
+use crate::DynResult;
+
+/// Default location of the answers file.
+pub const DEFAULT_PATH: &str = "./answers.toml";
+
+/// Answers keyed by (year, day, part).
+///
+/// ```
+/// use aoc20::answers::Answers;
+/// use aoc20::Answer;
+///
+/// let answers = Answers::parse("[day21]\npart2 = \"mxmxvkd,sqjhc\"").unwrap();
+/// assert_eq!(answers.get(2020, 21, 2), Some(&Answer::from("mxmxvkd,sqjhc")));
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Answers(pub BTreeMap<(usize, usize, usize), Answer>);
+
+impl Answers {
+    pub fn parse(s: &str) -> DynResult<Answers> {
+        let mut answers = BTreeMap::new();
+        let mut day = None;
+
+        for (i, ln) in s.split('\n').enumerate() {
+            let err = |msg: &str| format!("answers line {}: {}", i + 1, msg);
+
+            let ln = ln.trim();
+            if ln.is_empty() || ln.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = ln.strip_prefix('[') {
+                let key = header
+                    .strip_suffix(']')
+                    .and_then(parse_header)
+                    .ok_or_else(|| err("expected a table header like [day1] or [2019.day1]"))?;
+                day = Some(key);
+                continue;
+            }
+
+            let (year, day) = day.ok_or_else(|| err("answer outside of a [dayN] table"))?;
+            let (key, val) = ln
+                .split_once('=')
+                .ok_or_else(|| err("expected `partN = <answer>`"))?;
+            let part = key
+                .trim()
+                .strip_prefix("part")
+                .and_then(|n| n.parse::<usize>().ok())
+                .ok_or_else(|| err("expected key like part1 or part2"))?;
+
+            let val = val.trim();
+            let answer = if let Some(s) = val.strip_prefix('"') {
+                let s = s
+                    .strip_suffix('"')
+                    .ok_or_else(|| err("unterminated string"))?;
+                Answer::Str(s.replace("\\\"", "\"").replace("\\\\", "\\"))
+            } else {
+                Answer::Int(val.parse().map_err(|_| err("invalid integer"))?)
+            };
+
+            if answers.insert((year, day, part), answer).is_some() {
+                return Err(err("duplicate answer").into());
+            }
+        }
+
+        Ok(Answers(answers))
+    }
+
+    /// Load answers from disk. A missing file is treated as an empty set of
+    /// answers.
+    pub fn load(path: &Path) -> DynResult<Answers> {
+        match std::fs::read_to_string(path) {
+            Ok(s) => Answers::parse(&s),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(format!("Could not open {}: {}", path.to_string_lossy(), e).into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> DynResult<()> {
+        std::fs::write(path, self.to_string())
+            .map_err(|e| format!("Could not write {}: {}", path.to_string_lossy(), e).into())
+    }
+
+    pub fn get(&self, year: usize, day: usize, part: usize) -> Option<&Answer> {
+        self.0.get(&(year, day, part))
+    }
+}
+
+/// Parse a table header (e.g: `day1`, or `2019.day1`) into a (year, day).
+fn parse_header(header: &str) -> Option<(usize, usize)> {
+    let (year, day) = match header.split_once('.') {
+        Some((year, day)) => (year.parse().ok()?, day),
+        None => (DEFAULT_YEAR, header),
+    };
+    Some((year, day.strip_prefix("day")?.parse().ok()?))
+}
+
+impl core::fmt::Display for Answers {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut prev_day = None;
+        for (&(year, day, part), answer) in self.0.iter() {
+            if prev_day != Some((year, day)) {
+                if prev_day.is_some() {
+                    writeln!(f)?;
+                }
+                match year {
+                    DEFAULT_YEAR => writeln!(f, "[day{}]", day)?,
+                    _ => writeln!(f, "[{}.day{}]", year, day)?,
+                }
+                prev_day = Some((year, day));
+            }
+
+            match answer {
+                Answer::Int(n) => writeln!(f, "part{} = {}", part, n)?,
+                Answer::Str(s) => writeln!(
+                    f,
+                    "part{} = \"{}\"",
+                    part,
+                    s.replace('\\', "\\\\").replace('"', "\\\"")
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare each run against the recorded answers, printing a line per part.
+///
+/// Returns `true` if every run matched its recorded answer. Runs without a
+/// recorded answer count as failures, since nothing was actually checked.
+pub fn verify(runs: &[Run], answers: &Answers) -> bool {
+    let mut ok = true;
+    for run in runs {
+        let status = match (&run.answer, answers.get(run.year, run.day, run.part)) {
+            (Err(e), _) => {
+                ok = false;
+                format!("ERROR ({})", e)
+            }
+            (Ok(_), None) => {
+                ok = false;
+                "unrecorded".to_string()
+            }
+            (Ok(actual), Some(expected)) if actual == expected => "ok".to_string(),
+            (Ok(actual), Some(expected)) => {
+                ok = false;
+                format!("MISMATCH (expected {}, got {})", expected, actual)
+            }
+        };
+        println!(
+            "{} day {:>2} part {}: {}",
+            run.year, run.day, run.part, status
+        );
+    }
+    ok
+}
+
+/// Record the answers of each successful run, overwriting any existing
+/// answers for the same (day, part).
+pub fn record(runs: &[Run], answers: &mut Answers) {
+    for run in runs {
+        match &run.answer {
+            Ok(answer) => {
+                println!(
+                    "{} day {:>2} part {}: {}",
+                    run.year, run.day, run.part, answer
+                );
+                answers
+                    .0
+                    .insert((run.year, run.day, run.part), answer.clone());
+            }
+            Err(e) => println!(
+                "{} day {:>2} part {}: skipped ({})",
+                run.year, run.day, run.part, e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+# recorded answers
+[day1]
+part1 = 514579
+part2 = 241861950
+
+[day21]
+part1 = 5
+part2 = "mxmxvkd,sqjhc,\"fvjkl\""
+
+[2019.day1]
+part1 = 3394032
+"#;
+
+    #[test]
+    fn round_trip() {
+        let answers = Answers::parse(EXAMPLE).unwrap();
+        assert_eq!(answers.get(2020, 1, 2), Some(&Answer::Int(241861950)));
+        assert_eq!(answers.get(2019, 1, 1), Some(&Answer::Int(3394032)));
+        assert_eq!(answers.get(2019, 1, 2), None);
+        assert_eq!(
+            answers.get(2020, 21, 2),
+            Some(&Answer::Str("mxmxvkd,sqjhc,\"fvjkl\"".into()))
+        );
+        assert_eq!(Answers::parse(&answers.to_string()).unwrap(), answers);
+    }
+
+    #[test]
+    fn unrecorded_fails() {
+        let run = |part, answer: i128| Run {
+            year: 2020,
+            day: 1,
+            part,
+            title: "",
+            answer: Ok(Answer::Int(answer)),
+            parse: Default::default(),
+            solve: Default::default(),
+            alloc: None,
+        };
+        let answers = Answers::parse("[day1]\npart1 = 514579").unwrap();
+
+        assert!(verify(&[run(1, 514579)], &answers));
+        assert!(!verify(&[run(1, 1)], &answers));
+        assert!(!verify(&[run(1, 514579), run(2, 241861950)], &answers));
+        assert!(!verify(&[run(1, 514579)], &Answers::default()));
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(Answers::parse("part1 = 3").is_err());
+        assert!(Answers::parse("[day1]\npart1 = abc").is_err());
+        assert!(Answers::parse("[day1]\npart1 = 1\npart1 = 2").is_err());
+        assert!(Answers::parse("[dayX]").is_err());
+        assert!(Answers::parse("[x.day1]").is_err());
+    }
+}
@@ -0,0 +1,372 @@
+//! Authoring and storing day 8's handheld programs.
+//!
+//! Puzzle inputs use relative jump offsets, which are a pain to write (and
+//! maintain) by hand. [`assemble`] accepts a slightly friendlier syntax:
+//!
+//! ```text
+//! ; comments run to the end of the line
+//! start:
+//!     acc +1
+//!     jmp skip    ; labels are resolved to relative offsets
+//!     acc -99
+//! skip: jmp start
+//! ```
+//!
+//! Programs can also be stored in a compact binary encoding (see [`encode`]),
+//! and [`load`] reads a program in any of these formats. Everything which
+//! reads programs takes an [`Isa`], so extension instructions work too.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::day8::Instr;
+use crate::error::{InputError, OptionExt, ResultExt};
+use crate::isa::{Isa, Operand, MAX_ARGS};
+use crate::DynResult;
+
+/// Marks the start of an encoded program.
+pub const MAGIC: &[u8; 4] = b"HH8\x01";
+
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Assemble source (which may contain labels and comments) into a program.
+///
+/// ```
+/// use aoc20::asm;
+/// use aoc20::day8::Instr;
+/// use aoc20::isa::Isa;
+///
+/// let program = asm::assemble("top: acc +1 ; comment\n\njmp top", &Isa::handheld()).unwrap();
+/// assert_eq!(program, [Instr::Acc(1), Instr::Jmp(-1)]);
+/// ```
+pub fn assemble(source: &str, isa: &Isa) -> Result<Vec<Instr>, InputError> {
+    // lines containing instructions, with comments and labels stripped
+    let mut lines = Vec::new();
+    let mut labels = HashMap::new();
+    for ln in source.split('\n') {
+        let mut ln = ln.split(';').next().unwrap().trim();
+        while let Some((label, rest)) = ln.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                return Err(InputError::new(source, label, "invalid label"));
+            }
+            if labels.insert(label, lines.len()).is_some() {
+                return Err(InputError::new(source, label, "duplicate label"));
+            }
+            ln = rest.trim();
+        }
+        if !ln.is_empty() {
+            lines.push(ln);
+        }
+    }
+
+    let mut program = Vec::with_capacity(lines.len());
+    for (pc, ln) in lines.into_iter().enumerate() {
+        let mut words = ln.split_whitespace();
+        let name = words.next().unwrap();
+        let op = isa
+            .by_name(name)
+            .ok_or_at(source, name, &format!("invalid opcode: {}", name))?;
+
+        // swap labels for offsets, and let the opcode parse everything else
+        let mut operands = Vec::new();
+        for (i, word) in words.enumerate() {
+            let kind = op.operands.get(i).copied();
+            if is_label(word) && kind == Some(Operand::Offset) {
+                let target = *labels.get(word).ok_or_at(source, word, "undefined label")?;
+                operands.push(Cow::Owned(format!("{:+}", target as i64 - pc as i64)));
+            } else if is_label(word) && kind == Some(Operand::Imm) {
+                return Err(InputError::new(
+                    source,
+                    word,
+                    "labels can only be used as offsets",
+                ));
+            } else {
+                operands.push(Cow::Borrowed(word));
+            }
+        }
+        program.push(
+            op.parse(operands.iter().map(|w| w.as_ref()))
+                .at(source, ln)?,
+        );
+    }
+
+    Ok(program)
+}
+
+/// Turn a program back into source, with labels for every `jmp` target.
+///
+/// `nop`s keep their offsets, since (unlike `jmp`s) they don't go anywhere.
+/// Assembling the result produces the original program.
+pub fn disassemble(program: &[Instr]) -> String {
+    let target = |pc: usize, instr: &Instr| match *instr {
+        Instr::Jmp(offset) => {
+            let target = (pc as i64).checked_add(offset)?;
+            if (0..=program.len() as i64).contains(&target) {
+                Some(target as usize)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    let mut is_target = vec![false; program.len() + 1];
+    for (pc, instr) in program.iter().enumerate() {
+        if let Some(t) = target(pc, instr) {
+            is_target[t] = true;
+        }
+    }
+
+    let mut out = String::new();
+    for (pc, instr) in program.iter().enumerate() {
+        if is_target[pc] {
+            out += &format!("l{}:\n", pc);
+        }
+        match target(pc, instr) {
+            Some(t) => out += &format!("    jmp l{}\n", t),
+            None => out += &format!("    {}\n", instr),
+        }
+    }
+    if is_target[program.len()] {
+        out += &format!("l{}:\n", program.len());
+    }
+    out
+}
+
+/// Append `n` as a LEB128 varint.
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> DynResult<u64> {
+    let mut n = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&b, rest) = bytes.split_first().ok_or("unexpected end of program")?;
+        *bytes = rest;
+        n |= ((b & 0x7f) as u64)
+            .checked_shl(shift)
+            .filter(|v| v >> shift == (b & 0x7f) as u64)
+            .ok_or("varint overflowed")?;
+        if b & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err("varint overflowed".into())
+}
+
+/// Encode a program as [`MAGIC`], followed by the number of instructions,
+/// followed by each instruction's opcode byte (see [`OpDef::code`]) and
+/// (zigzag encoded) operands. All numbers are LEB128 varints, so most
+/// instructions take 2 bytes.
+///
+/// ```
+/// use aoc20::asm;
+/// use aoc20::day8::Instr;
+/// use aoc20::isa::Isa;
+///
+/// let program = [Instr::Nop(0), Instr::Acc(-1), Instr::Jmp(300)];
+/// let bytes = asm::encode(&program);
+/// assert_eq!(bytes.len(), 4 + 1 + 2 + 2 + 3);
+/// assert_eq!(asm::decode(&bytes, &Isa::handheld()).unwrap(), program);
+/// ```
+///
+/// [`OpDef::code`]: crate::isa::OpDef::code
+pub fn encode(program: &[Instr]) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    write_varint(&mut out, program.len() as u64);
+    for instr in program {
+        let (op, args) = instr.parts();
+        out.push(op.code);
+        for (&n, _) in args.iter().zip(op.operands) {
+            write_varint(&mut out, ((n << 1) ^ (n >> 63)) as u64);
+        }
+    }
+    out
+}
+
+/// Decode a program produced by [`encode`], using `isa` to look up opcodes.
+pub fn decode(bytes: &[u8], isa: &Isa) -> DynResult<Vec<Instr>> {
+    let mut bytes = bytes
+        .strip_prefix(&MAGIC[..])
+        .ok_or("not an encoded program")?;
+
+    let len = read_varint(&mut bytes)?;
+    // every instruction takes at least a byte, so don't trust huge lengths
+    let mut program = Vec::with_capacity((len as usize).min(bytes.len()));
+    for _ in 0..len {
+        let (&code, rest) = bytes.split_first().ok_or("unexpected end of program")?;
+        bytes = rest;
+        let op = isa
+            .by_code(code)
+            .ok_or_else(|| format!("invalid opcode: {}", code))?;
+
+        let mut args = [0; MAX_ARGS];
+        for (arg, _) in args.iter_mut().zip(op.operands) {
+            let n = read_varint(&mut bytes)?;
+            *arg = (n >> 1) as i64 ^ -((n & 1) as i64);
+        }
+        program.push(op.decode(args)?);
+    }
+
+    if !bytes.is_empty() {
+        return Err("trailing bytes after program".into());
+    }
+    Ok(program)
+}
+
+/// Load a program from a file, which is either encoded (see [`encode`]) or
+/// source (see [`assemble`]).
+pub fn load(path: &Path, isa: &Isa) -> DynResult<Vec<Instr>> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("Could not open {}: {}", path.to_string_lossy(), e))?;
+    if bytes.starts_with(MAGIC) {
+        return decode(&bytes, isa);
+    }
+
+    let source = String::from_utf8(bytes)?;
+    Ok(assemble(source.trim_end(), isa)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::day8;
+    use crate::isa;
+    use crate::util::rng::Rng;
+
+    fn assemble(source: &str) -> Result<Vec<Instr>, InputError> {
+        super::assemble(source, &Isa::handheld())
+    }
+
+    fn decode(bytes: &[u8]) -> DynResult<Vec<Instr>> {
+        super::decode(bytes, &Isa::handheld())
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for instr in &[Instr::Nop(0), Instr::Acc(-7), Instr::Jmp(i64::MAX)] {
+            assert_eq!(Instr::parse(&instr.to_string()).unwrap(), *instr);
+        }
+
+        let input = day8::generate(&mut Rng::new(0), 100);
+        let program = assemble(&input).unwrap();
+        assert_eq!(
+            program.iter().map(Instr::to_string).collect::<Vec<_>>(),
+            input.split('\n').collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn labels() {
+        let source = "
+; count down from 3
+start:
+    acc +3
+loop: acc -1 ; not really a loop
+    nop end
+    jmp loop
+end:";
+        assert_eq!(
+            assemble(source.trim()).unwrap(),
+            [Instr::Acc(3), Instr::Acc(-1), Instr::Nop(2), Instr::Jmp(-2)]
+        );
+
+        let err = |source| assemble(source).unwrap_err().msg;
+        assert_eq!(err("jmp nowhere"), "undefined label");
+        assert_eq!(err("a:\na: nop +0"), "duplicate label");
+        assert_eq!(err("1a: nop +0"), "invalid label");
+        assert_eq!(err("a: acc a"), "labels can only be used as offsets");
+        assert_eq!(err("hlt +1"), "invalid opcode: hlt");
+    }
+
+    #[test]
+    fn extensions() {
+        let isa = Isa::extended();
+        let source = "
+    set r1 +3
+loop:
+    add r1
+    jz done
+    out +0
+    jmp loop
+done: hlt";
+        let program = super::assemble(source.trim(), &isa).unwrap();
+        assert_eq!(program[0], Instr::Ext(&isa::SET, [1, 3]));
+        assert_eq!(program[2], Instr::Ext(&isa::JZ, [3, 0]));
+        assert_eq!(
+            super::assemble(&disassemble(&program), &isa).unwrap(),
+            program
+        );
+
+        let bytes = encode(&program);
+        assert_eq!(super::decode(&bytes, &isa).unwrap(), program);
+        assert_eq!(
+            decode(&bytes).unwrap_err().to_string(),
+            format!("invalid opcode: {}", isa::SET.code)
+        );
+
+        // register operands are range checked
+        let mut bad_reg = encode(&[Instr::Ext(&isa::LD, [1, 0])]);
+        *bad_reg.last_mut().unwrap() = 2 * isa::NUM_REGS as u8;
+        assert!(super::decode(&bad_reg, &isa).is_err());
+    }
+
+    #[test]
+    fn disassemble_round_trips() {
+        let program = assemble(day8::EXAMPLES[0].trim()).unwrap();
+        assert_eq!(
+            disassemble(&program),
+            "    nop +0\nl1:\n    acc +1\n    jmp l6\nl3:\n    acc +3\n    jmp l1\n    acc -99\n\
+             l6:\n    acc +1\n    jmp l3\n    acc +6\n"
+        );
+
+        for seed in 0..20 {
+            let input = day8::generate(&mut Rng::new(seed), 200);
+            let program = assemble(&input).unwrap();
+            assert_eq!(assemble(&disassemble(&program)).unwrap(), program);
+        }
+    }
+
+    #[test]
+    fn encoding() {
+        let program = [
+            Instr::Nop(i64::MIN),
+            Instr::Acc(i64::MAX),
+            Instr::Jmp(-1),
+            Instr::Acc(0),
+        ];
+        let bytes = encode(&program);
+        assert_eq!(decode(&bytes).unwrap(), program);
+
+        // every truncation fails cleanly
+        for i in 0..bytes.len() {
+            assert!(decode(&bytes[..i]).is_err());
+        }
+
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert!(decode(&extra).is_err());
+
+        let mut bad_op = encode(&[Instr::Nop(1)]);
+        bad_op[5] = 200;
+        assert_eq!(
+            decode(&bad_op).unwrap_err().to_string(),
+            "invalid opcode: 200"
+        );
+
+        let mut huge = MAGIC.to_vec();
+        huge.extend(&[0xff; 11]);
+        assert!(decode(&huge).is_err());
+    }
+}
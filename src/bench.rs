@@ -0,0 +1,171 @@
+//! Statistical benchmarking of individual solutions.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::options::Options;
+use crate::runner::fmt_duration;
+use crate::solution::Solution;
+use crate::DynResult;
+
+/// Summary statistics over a set of benchmark samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Calculate stats from a (non-empty) list of samples.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Stats {
+        assert!(
+            !samples.is_empty(),
+            "cannot calculate stats without samples"
+        );
+        samples.sort();
+
+        // nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = (p * samples.len() as f64).ceil() as usize;
+            samples[rank.saturating_sub(1)]
+        };
+
+        Stats {
+            runs: samples.len(),
+            min: samples[0],
+            median: percentile(0.5),
+            p95: percentile(0.95),
+            max: samples[samples.len() - 1],
+        }
+    }
+
+    pub fn print(&self, input_len: usize) {
+        println!("runs:   {}", self.runs);
+        println!("min:    {}", fmt_duration(self.min));
+        println!("median: {}", fmt_duration(self.median));
+        println!("p95:    {}", fmt_duration(self.p95));
+        println!("max:    {}", fmt_duration(self.max));
+
+        let secs = self.median.as_secs_f64();
+        if secs > 0.0 {
+            println!(
+                "throughput: {:.2} runs/s, {:.2} MB/s",
+                1.0 / secs,
+                input_len as f64 / secs / 1e6
+            );
+        }
+    }
+
+    /// Serialize stats as a simple `key = value` baseline file.
+    pub fn to_baseline(&self) -> String {
+        format!(
+            "runs = {}\nmin_ns = {}\nmedian_ns = {}\np95_ns = {}\nmax_ns = {}\n",
+            self.runs,
+            self.min.as_nanos(),
+            self.median.as_nanos(),
+            self.p95.as_nanos(),
+            self.max.as_nanos(),
+        )
+    }
+
+    pub fn from_baseline(s: &str) -> DynResult<Stats> {
+        let get = |key: &str| -> DynResult<u64> {
+            let val = s
+                .split('\n')
+                .filter_map(|ln| ln.split_once('='))
+                .find(|(k, _)| k.trim() == key)
+                .ok_or_else(|| format!("baseline is missing {}", key))?
+                .1;
+            Ok(val.trim().parse::<u64>()?)
+        };
+
+        Ok(Stats {
+            runs: get("runs")? as usize,
+            min: Duration::from_nanos(get("min_ns")?),
+            median: Duration::from_nanos(get("median_ns")?),
+            p95: Duration::from_nanos(get("p95_ns")?),
+            max: Duration::from_nanos(get("max_ns")?),
+        })
+    }
+
+    pub fn save_baseline(&self, path: &Path) -> DynResult<()> {
+        std::fs::write(path, self.to_baseline())
+            .map_err(|e| format!("Could not write {}: {}", path.to_string_lossy(), e).into())
+    }
+
+    pub fn load_baseline(path: &Path) -> DynResult<Stats> {
+        let s = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not open {}: {}", path.to_string_lossy(), e))?;
+        Stats::from_baseline(&s)
+    }
+
+    /// Relative change in median runtime compared to a baseline (e.g: `0.1`
+    /// means this run is 10% slower than the baseline).
+    pub fn change_from(&self, baseline: &Stats) -> f64 {
+        let base = baseline.median.as_secs_f64();
+        if base == 0.0 {
+            return 0.0;
+        }
+        (self.median.as_secs_f64() - base) / base
+    }
+}
+
+/// Run a solution `warmup` times (discarding the results), followed by `runs`
+/// timed runs.
+pub fn bench(
+    sol: &dyn Solution,
+    part: usize,
+    input: &str,
+    opts: &Options,
+    warmup: usize,
+    runs: usize,
+) -> DynResult<Stats> {
+    if runs == 0 {
+        return Err("must bench at least one run".into());
+    }
+
+    for _ in 0..warmup {
+        sol.part(part, input, opts)?;
+    }
+
+    let mut samples = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let start = Instant::now();
+        sol.part(part, input, opts)?;
+        samples.push(start.elapsed());
+    }
+
+    Ok(Stats::from_samples(samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn stats() {
+        let stats = Stats::from_samples((1..=20).rev().map(ms).collect());
+        assert_eq!(stats.runs, 20);
+        assert_eq!(stats.min, ms(1));
+        assert_eq!(stats.median, ms(10));
+        assert_eq!(stats.p95, ms(19));
+        assert_eq!(stats.max, ms(20));
+    }
+
+    #[test]
+    fn baseline() {
+        let stats = Stats::from_samples(vec![ms(3), ms(1), ms(2)]);
+        let baseline = Stats::from_baseline(&stats.to_baseline()).unwrap();
+        assert_eq!(baseline, stats);
+
+        let slower = Stats::from_samples(vec![ms(3), ms(3), ms(3)]);
+        assert!((slower.change_from(&baseline) - 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,200 @@
+//! Control flow analysis of day 8's handheld programs.
+//!
+//! Every instruction has exactly one successor, so a program's control flow
+//! graph is just a function from each pc to the next. Working backwards from
+//! the end of the program finds every pc which leads to termination, which is
+//! enough to repair a looping program without re-running it once per flip.
+
+use std::collections::HashSet;
+
+use crate::day8::Instr;
+
+/// The control flow graph of a program.
+///
+/// Node `program.len()` represents termination. Instructions which jump
+/// anywhere else outside the program have no successor, and neither do
+/// extension instructions (see [`crate::isa`]), which aren't analysed.
+///
+/// ```
+/// use aoc20::cfg::Cfg;
+/// use aoc20::day8::Vm;
+///
+/// let vm = Vm::new("nop +0\njmp +2\njmp +0\nacc +1").unwrap();
+/// let cfg = Cfg::new(&vm.program);
+/// assert_eq!(cfg.successor(1), Some(3));
+/// assert_eq!(cfg.terminating(), [true, true, false, true, true]);
+/// ```
+pub struct Cfg {
+    succ: Vec<Option<usize>>,
+    preds: Vec<Vec<usize>>,
+}
+
+impl Cfg {
+    pub fn new(program: &[Instr]) -> Cfg {
+        let succ = (0..program.len())
+            .map(|pc| next_pc(program.len(), pc, program[pc]))
+            .collect::<Vec<_>>();
+
+        let mut preds = vec![Vec::new(); program.len() + 1];
+        for (pc, next) in succ.iter().enumerate() {
+            if let Some(next) = *next {
+                preds[next].push(pc);
+            }
+        }
+
+        Cfg { succ, preds }
+    }
+
+    /// The pc executed after `pc`, if it's within the program (or just past
+    /// the end of it).
+    pub fn successor(&self, pc: usize) -> Option<usize> {
+        *self.succ.get(pc)?
+    }
+
+    /// Which nodes lead to termination, found by walking the graph backwards
+    /// from the end of the program.
+    pub fn terminating(&self) -> Vec<bool> {
+        let end = self.succ.len();
+        let mut seen = vec![false; end + 1];
+        seen[end] = true;
+
+        let mut stack = vec![end];
+        while let Some(pc) = stack.pop() {
+            for &pred in &self.preds[pc] {
+                if !seen[pred] {
+                    seen[pred] = true;
+                    stack.push(pred);
+                }
+            }
+        }
+        seen
+    }
+
+    /// The pcs executed by the program, in order, stopping just before
+    /// anything would be executed a second time (or the program ends).
+    pub fn path(&self) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pc = Some(0);
+        while let Some(p) = pc.filter(|&p| p < self.succ.len()) {
+            if !seen.insert(p) {
+                break;
+            }
+            path.push(p);
+            pc = self.succ[p];
+        }
+        path
+    }
+}
+
+fn next_pc(len: usize, pc: usize, instr: Instr) -> Option<usize> {
+    let offset = match instr {
+        Instr::Jmp(offset) => offset,
+        Instr::Nop(_) | Instr::Acc(_) => 1,
+        Instr::Ext(..) => return None,
+    };
+    let next = (pc as i64).checked_add(offset)?;
+    if (0..=len as i64).contains(&next) {
+        Some(next as usize)
+    } else {
+        None
+    }
+}
+
+/// Find the `jmp` / `nop` which, when flipped, makes a looping program
+/// terminate, in O(n).
+///
+/// Only instructions which are actually executed matter, and flipping one is
+/// a fix iff its new successor already leads to termination (since getting
+/// there via the flipped instruction would mean looping forever). Returns
+/// `None` if no flip works, or if the program already terminates.
+pub fn find_fix(program: &[Instr]) -> Option<usize> {
+    find_fixes(program).into_iter().next()
+}
+
+/// Every instruction which [`find_fix`] could have picked, in the order
+/// they're executed. Empty if the program already terminates.
+pub fn find_fixes(program: &[Instr]) -> Vec<usize> {
+    let cfg = Cfg::new(program);
+    let terminating = cfg.terminating();
+    if terminating[0] {
+        return Vec::new();
+    }
+
+    cfg.path()
+        .into_iter()
+        .filter(|&pc| {
+            let next = program[pc]
+                .flipped()
+                .and_then(|instr| next_pc(program.len(), pc, instr));
+            matches!(next, Some(next) if terminating[next])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::day8::Vm;
+    use crate::util::rng::Rng;
+
+    /// Flip every instruction, one at a time, and see which ones terminate.
+    fn brute_force(program: &[Instr]) -> Vec<usize> {
+        let mut vm = Vm::from_program(program.to_vec());
+        (0..program.len())
+            .filter(|&i| {
+                let instr = match program[i].flipped() {
+                    Some(instr) => instr,
+                    None => return false,
+                };
+                vm.reset();
+                vm.program[i] = instr;
+                let terminates = vm.run_with_loop_detect().unwrap_or(false);
+                vm.program[i] = program[i];
+                terminates
+            })
+            .collect()
+    }
+
+    #[test]
+    fn example() {
+        let vm = Vm::new(crate::day8::EXAMPLES[0].trim()).unwrap();
+        assert_eq!(Cfg::new(&vm.program).path(), [0, 1, 2, 6, 7, 3, 4]);
+        assert_eq!(find_fix(&vm.program), Some(7));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for seed in 0..500 {
+            let mut rng = Rng::new(seed);
+            let len = rng.range(1..20);
+            let program = (0..len)
+                .map(|_| {
+                    let n = rng.range_i64(-(len as i64) - 1..len as i64 + 2);
+                    match rng.range(0..3) {
+                        0 => Instr::Nop(n),
+                        1 => Instr::Acc(n),
+                        _ => Instr::Jmp(n),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mut vm = Vm::from_program(program.clone());
+            if let Ok(true) = vm.run_with_loop_detect() {
+                // already terminates, so there's nothing to fix
+                assert_eq!(find_fix(&program), None, "seed {}", seed);
+                continue;
+            }
+
+            let fixes = brute_force(&program);
+            let mut found = find_fixes(&program);
+            found.sort_unstable();
+            assert_eq!(found, fixes, "seed {}", seed);
+            match find_fix(&program) {
+                Some(fix) => assert!(fixes.contains(&fix), "seed {}", seed),
+                None => assert_eq!(fixes, [], "seed {}", seed),
+            }
+        }
+    }
+}
@@ -1,39 +1,75 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input
+        let input = input
             .split('\n')
-            .map(|s| s.parse::<usize>())
-            .collect::<Result<Vec<_>, _>>()?
+            .map(|s| s.parse::<usize>().at(input, s))
+            .collect::<Result<Vec<_>, _>>()?;
+        aoc::mark_parsed();
+        input
     }};
 }
 
 fn find<const GROUP_SIZE: usize>(nums: &[usize], sum: usize) -> Option<usize> {
     for v in nums.combinations::<GROUP_SIZE>() {
-        if v.into_iter().sum::<usize>() == sum {
+        if v.iter().try_fold(0usize, |a, &b| a.checked_add(*b)) == Some(sum) {
             return Some(v.iter().copied().product());
         }
     }
     None
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     find::<2>(&input, 2020).ok_or_else(|| "invalid input".into())
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     find::<3>(&input, 2020).ok_or_else(|| "invalid input".into())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate an expense report with `size` entries, containing exactly one pair
+/// and exactly one triple of entries which sum to 2020.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let count_sums = |entries: &[usize], n: usize| {
+        entries
+            .iter()
+            .combinations(n)
+            .filter(|c| c.iter().copied().sum::<usize>() == 2020)
+            .count()
+    };
+
+    // the pair and triple only use entries >= 500, so they can't combine with
+    // the filler entries (all > 1020) to form any other pairs / triples
+    let mut entries = loop {
+        let a = rng.range(500..1010);
+        let (x, y) = (rng.range(500..760), rng.range(500..760));
+        let entries = vec![a, 2020 - a, x, y, 2020 - x - y];
+        if count_sums(&entries, 2) == 1 && count_sums(&entries, 3) == 1 {
+            break entries;
+        }
+    };
+
+    while entries.len() < size {
+        let n = rng.range(1021..2000);
+        if !entries.contains(&(2020 - n)) {
+            entries.push(n);
+        }
+    }
+
+    rng.shuffle(&mut entries);
+    entries.iter().join("\n")
+}
 
-    const EXAMPLE_1: &str = "
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = "
 1721
 979
 366
@@ -42,13 +78,19 @@ mod tests {
 1456
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::prop;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = 514579;
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -57,6 +99,33 @@ mod tests {
         let expected = 241861950;
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }
+
+    fn reference(input: &str, n: usize) -> usize {
+        let nums = input
+            .split('\n')
+            .map(|n| n.parse::<usize>().unwrap())
+            .collect::<Vec<_>>();
+        for i in 0..nums.len() {
+            for j in i + 1..nums.len() {
+                if n == 2 && nums[i] + nums[j] == 2020 {
+                    return nums[i] * nums[j];
+                }
+                for k in j + 1..nums.len() {
+                    if n == 3 && nums[i] + nums[j] + nums[k] == 2020 {
+                        return nums[i] * nums[j] * nums[k];
+                    }
+                }
+            }
+        }
+        panic!("no solution")
+    }
+
+    #[test]
+    fn prop_matches_reference() {
+        let opts = Options::default();
+        prop::check(100, generate, |i| q1(i, &opts), |i| reference(i, 2));
+        prop::check(100, generate, |i| q2(i, &opts), |i| reference(i, 3));
     }
 }
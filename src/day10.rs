@@ -1,19 +1,25 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
-        let mut heap = $input
+        let input = $input;
+        let mut heap = input
             .split('\n')
-            .map(|ln| ln.parse::<usize>())
+            .map(|ln| ln.parse::<usize>().at(input, ln))
             .collect::<Result<BinaryHeap<_>, _>>()?;
 
         heap.push(0);
-        heap.push(heap.peek().unwrap() + 3);
-        heap.into_sorted_vec()
+        let device = heap.peek().unwrap().checked_add(3).ok_or("joltage overflowed")?;
+        heap.push(device);
+        let input = heap.into_sorted_vec();
+        aoc::mark_parsed();
+        input
     }};
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     let (ones, threes) =
         input
@@ -28,38 +34,48 @@ pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
     Ok(ones * threes)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
 
-    let combos = input
-        .array_windows()
-        .map(|[a, b]| b - a)
-        .collect::<Vec<_>>()
-        .split(|v| *v == 3)
-        .map(|run| match run.len() {
-            0 | 1 => 1,
-            2 => 2,
-            3 => 4,
-            4 => 7,
-            // HACK: AoC input doesn't have any runs greater than 4. As such, I
-            // can just manually enumerate the combos within each run, and
-            // hard-code them.
-            //
-            // I've spent way too much time on this question, and I don't want
-            // to spend more time coming up with the general equation for how
-            // to calculate the number of permutations a run can have.
-            _ => unimplemented!(),
-        })
-        .product();
-
-    Ok(combos)
+    // AoC input only has gaps of 1 and 3, which made it tempting to multiply
+    // together the ways through each run of 1s. Counting the ways to reach
+    // each adapter from those within 3 jolts below it works for any gaps.
+    let mut ways = vec![0usize; input.len()];
+    ways[0] = 1;
+    for i in 1..input.len() {
+        let mut total = 0usize;
+        for j in (0..i).rev().take_while(|&j| input[i] - input[j] <= 3) {
+            total = total.checked_add(ways[j]).ok_or("too many combinations")?;
+        }
+        ways[i] = total;
+    }
+    Ok(*ways.last().unwrap())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate `size` adapters (at most 100, to avoid overflow), where adjacent
+/// joltages differ by 1 to 3, with at most 4 differences of 1 in a row.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut jolts = Vec::new();
+    let (mut jolt, mut ones) = (0, 0);
+    for _ in 0..size.clamp(1, 100) {
+        let diff = if ones < 4 && rng.chance(2, 3) {
+            1
+        } else {
+            rng.range(2..4)
+        };
+        ones = if diff == 1 { ones + 1 } else { 0 };
+        jolt += diff;
+        jolts.push(jolt);
+    }
+
+    rng.shuffle(&mut jolts);
+    jolts.iter().join("\n")
+}
 
-    const EXAMPLE_1: &str = "
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1, EXAMPLE_2];
+
+const EXAMPLE_1: &str = "
 16
 10
 15
@@ -73,7 +89,7 @@ mod tests {
 4
 ";
 
-    const EXAMPLE_2: &str = "
+const EXAMPLE_2: &str = "
 28
 33
 18
@@ -107,13 +123,19 @@ mod tests {
 3
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::prop;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 7 * 5 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -122,7 +144,7 @@ mod tests {
         let expected = { 22 * 10 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -131,7 +153,7 @@ mod tests {
         let expected = { 8 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -140,6 +162,71 @@ mod tests {
         let expected = { 19208 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }
+
+    #[test]
+    fn q2_gaps_of_2() {
+        let input = "1\n3\n4\n6";
+        assert_eq!(q2(input, &Options::default()).unwrap(), reference_q2(input));
+        assert_eq!(q2(input, &Options::default()).unwrap(), 5);
+    }
+
+    fn reference_jolts(input: &str) -> Vec<usize> {
+        let mut jolts = input
+            .split('\n')
+            .map(|n| n.parse().unwrap())
+            .collect::<Vec<_>>();
+        jolts.push(0);
+        jolts.sort_unstable();
+        jolts.push(jolts.last().unwrap() + 3);
+        jolts
+    }
+
+    fn reference_q1(input: &str) -> usize {
+        let jolts = reference_jolts(input);
+        let diffs = (1..jolts.len()).map(|i| jolts[i] - jolts[i - 1]);
+        diffs.clone().filter(|&d| d == 1).count() * diffs.filter(|&d| d == 3).count()
+    }
+
+    /// Exhaustively count every path from the outlet to the device.
+    fn reference_q2(input: &str) -> usize {
+        fn paths(jolts: &[usize], from: usize) -> usize {
+            if from == jolts.len() - 1 {
+                return 1;
+            }
+            (from + 1..jolts.len())
+                .take_while(|&i| jolts[i] - jolts[from] <= 3)
+                .map(|i| paths(jolts, i))
+                .sum()
+        }
+        paths(&reference_jolts(input), 0)
+    }
+
+    #[test]
+    fn prop_matches_reference() {
+        let opts = Options::default();
+        prop::check(30, generate, |i| q1(i, &opts), reference_q1);
+        prop::check(30, generate, |i| q2(i, &opts), reference_q2);
+    }
+
+    /// Like `generate`, but mostly 1s, with no cap on how many come in a row
+    /// (`reference_q2` is exponential in run length, so keep `size` small).
+    fn generate_long_runs(rng: &mut Rng, size: usize) -> String {
+        let mut jolt = 0;
+        let mut jolts = (0..size)
+            .map(|_| {
+                jolt += if rng.chance(9, 10) { 1 } else { rng.range(2..4) };
+                jolt
+            })
+            .collect::<Vec<_>>();
+        rng.shuffle(&mut jolts);
+        jolts.iter().join("\n")
+    }
+
+    #[test]
+    fn prop_long_runs() {
+        let opts = Options::default();
+        prop::check(24, generate_long_runs, |i| q2(i, &opts), reference_q2);
     }
 }
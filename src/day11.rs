@@ -1,47 +1,47 @@
 use crate::prelude::*;
+use crate::util::grid;
+
+pub const OPTIONS: &[Opt] = &[];
 
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input
-            .split('\n')
-            .map(|ln| ln.as_bytes().to_vec())
-            .collect::<Vec<_>>()
+        let seats = Grid::parse(input, |c| match c {
+            '.' | 'L' | '#' => Ok(c),
+            _ => Err("expected '.', 'L', or '#'"),
+        })?;
+        aoc::mark_parsed();
+        seats
     }};
 }
 
 fn count_stable(
-    input: Vec<Vec<u8>>,
-    visible: impl Fn(&[Vec<u8>], (usize, usize)) -> usize,
+    input: Grid<char>,
+    visible: impl Fn(&Grid<char>, (usize, usize)) -> usize,
     empty_cuttoff: usize,
-) -> DynResult<usize> {
+) -> usize {
     let mut curr = input;
     let mut next = curr.clone(); // clone to maintain dimensions
 
     loop {
-        // eprintln!();
+        // eprintln!("{}\n", curr);
         let mut stable = true;
-        for (r, row) in curr.iter().enumerate() {
-            // eprintln!();
-            for (c, seat) in row.iter().enumerate() {
-                // eprint!("{}", *seat as char);
-                next[r][c] = *seat; // copy over state from curr
-                match seat {
-                    b'L' => {
-                        if visible(&curr, (r, c)) == 0 {
-                            next[r][c] = b'#';
-                            stable = false;
-                        }
+        for (pos, &seat) in curr.enumerate() {
+            next[pos] = seat; // copy over state from curr
+            match seat {
+                'L' => {
+                    if visible(&curr, pos) == 0 {
+                        next[pos] = '#';
+                        stable = false;
                     }
-                    b'#' => {
-                        if visible(&curr, (r, c)) >= empty_cuttoff {
-                            next[r][c] = b'L';
-                            stable = false;
-                        }
+                }
+                '#' => {
+                    if visible(&curr, pos) >= empty_cuttoff {
+                        next[pos] = 'L';
+                        stable = false;
                     }
-                    b'.' => continue,
-                    _ => return Err("invalid input".into()),
                 }
+                _ => continue,
             }
         }
 
@@ -52,66 +52,32 @@ fn count_stable(
         }
     }
 
-    let occupied = curr
-        .into_iter()
-        .flat_map(|v| v.into_iter())
-        .filter(|c| *c == b'#')
-        .count();
-
-    Ok(occupied)
+    curr.iter().filter(|&&c| c == '#').count()
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
-    fn adj_count(input: &[Vec<u8>], (r, c): (usize, usize)) -> usize {
-        let rows = r.saturating_sub(1)..=(r.saturating_add(1).min(input.len() - 1));
-        let cols = c.saturating_sub(1)..=c.saturating_add(1).min(input[0].len() - 1);
-        rows.cartesian_product(cols)
-            .filter(|rc| *rc != (r, c))
-            .filter(|&(r, c)| input[r][c] == b'#')
-            .count()
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
+    fn adj_count(seats: &Grid<char>, pos: (usize, usize)) -> usize {
+        seats.neighbors8(pos).filter(|&p| seats[p] == '#').count()
     }
 
     let input = munge_input!(input);
-    count_stable(input, adj_count, 4)
+    Ok(count_stable(input, adj_count, 4))
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
 
     // calculate visibility map (i.e: list of positions to check for each seat)
     let mut visibility: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
 
-    let vecs = (-1..=1)
-        .cartesian_product(-1..=1)
-        .filter(|rc| *rc != (0, 0))
-        .collect::<Vec<_>>();
-    assert_eq!(vecs.len(), 8);
-
-    for (base_r, row) in input.iter().enumerate() {
-        for (base_c, seat) in row.iter().enumerate() {
-            match seat {
-                b'.' => continue,
-                b'L' | b'#' => {}
-                _ => return Err("invalid input".into()),
-            }
+    for (pos, &seat) in input.enumerate() {
+        if seat == '.' {
+            continue;
+        }
 
-            for &(dr, dc) in vecs.iter() {
-                let (mut r, mut c) = (base_r, base_c);
-                'raycast: loop {
-                    r = r.wrapping_add(dr as usize);
-                    c = c.wrapping_add(dc as usize);
-                    if let Some(seat) = input.get(r).and_then(|row| row.get(c)) {
-                        match seat {
-                            b'.' => continue 'raycast,
-                            b'L' | b'#' => {
-                                visibility.entry((base_r, base_c)).or_default().push((r, c));
-                                break 'raycast;
-                            }
-                            _ => return Err("invalid input".into()),
-                        }
-                    }
-                    break;
-                }
+        for &dir in grid::DIRS8.iter() {
+            if let Some(seen) = input.ray(pos, dir).find(|&p| input[p] != '.') {
+                visibility.entry(pos).or_default().push(seen);
             }
         }
     }
@@ -119,23 +85,32 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
     // now that it's calculated, freeze it
     let visibility = visibility;
 
-    let visible_count = |input: &[Vec<u8>], (r, c): (usize, usize)| -> usize {
-        visibility
-            .get(&(r, c))
-            .unwrap()
-            .iter()
-            .filter(|&(r, c)| input[*r][*c] == b'#')
-            .count()
+    let visible_count = |seats: &Grid<char>, pos: (usize, usize)| -> usize {
+        // seats with nothing in sight are never added to the map
+        visibility.get(&pos).map_or(0, |seen| {
+            seen.iter().filter(|&&p| seats[p] == '#').count()
+        })
     };
 
-    count_stable(input, visible_count, 5)
+    Ok(count_stable(input, visible_count, 5))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate a `size` x `size` seat layout.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.chance(1, 5) { '.' } else { 'L' })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
 
-    const EXAMPLE_1: &str = "
+const EXAMPLE_1: &str = "
 L.LL.LL.LL
 LLLLLLL.LL
 L.L.L..L..
@@ -148,13 +123,17 @@ L.LLLLLL.L
 L.LLLLL.LL
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 37 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -163,6 +142,6 @@ L.LLLLL.LL
         let expected = { 26 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
@@ -1,25 +1,23 @@
 use crate::prelude::*;
 
-#[derive(Debug, Copy, Clone)]
-enum Dir {
-    N = 0,
-    E = 1,
-    S = 2,
-    W = 3,
-}
+pub const OPTIONS: &[Opt] = &[];
+
+const NORTH: Vector<2> = Point([0, 1]);
+const SOUTH: Vector<2> = Point([0, -1]);
+const EAST: Vector<2> = Point([1, 0]);
+const WEST: Vector<2> = Point([-1, 0]);
 
 enum Act {
-    Dir(Dir, isize),
-    Rotate(isize), // normalized clockwise
+    Move(Vector<2>, isize),
+    Rotate(isize), // clockwise quarter turns
     Forward(isize),
 }
 
 impl std::str::FromStr for Act {
-    type Err = Box<dyn std::error::Error>;
+    type Err = Box<dyn std::error::Error + Send + Sync>;
     fn from_str(s: &str) -> DynResult<Act> {
-        let (act, val) = s.split_at(1);
-        let val = val.parse::<isize>()?;
-        let act = act.chars().next().ok_or("invalid input")?;
+        let act = s.chars().next().ok_or("invalid input")?;
+        let val = s[act.len_utf8()..].parse::<isize>()?;
 
         if val < 0 {
             return Err("vals must be positive integers".into());
@@ -30,12 +28,12 @@ impl std::str::FromStr for Act {
         }
 
         let act = match act {
-            'N' => Act::Dir(Dir::N, val),
-            'S' => Act::Dir(Dir::S, val),
-            'E' => Act::Dir(Dir::E, val),
-            'W' => Act::Dir(Dir::W, val),
-            'R' => Act::Rotate(val),
-            'L' => Act::Rotate(-val + 360),
+            'N' => Act::Move(NORTH, val),
+            'S' => Act::Move(SOUTH, val),
+            'E' => Act::Move(EAST, val),
+            'W' => Act::Move(WEST, val),
+            'R' => Act::Rotate(val / 90 % 4),
+            'L' => Act::Rotate(-(val / 90 % 4)),
             'F' => Act::Forward(val),
             _ => return Err("invalid action".into()),
         };
@@ -45,112 +43,118 @@ impl std::str::FromStr for Act {
 
 trait Ship {
     fn do_act(&mut self, act: Act) -> DynResult<()>;
-    fn manhattan_dist(&self) -> usize;
+    fn pos(&self) -> Point<2>;
 
-    fn run(&mut self, acts: impl Iterator<Item = DynResult<Act>>) -> DynResult<usize> {
+    fn run(&mut self, acts: Vec<Act>) -> DynResult<usize> {
         for act in acts {
-            self.do_act(act?)?
+            self.do_act(act)?
         }
-        Ok(self.manhattan_dist())
+        Ok(self.pos().checked_manhattan().ok_or("ship sailed too far")?)
     }
 }
 
+/// Move `pos` `val` units in the given direction.
+fn step(pos: &mut Point<2>, dir: Vector<2>, val: isize) -> DynResult<()> {
+    *pos = (dir.checked_mul(val))
+        .and_then(|d| pos.checked_add(d))
+        .ok_or("ship sailed too far")?;
+    Ok(())
+}
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input.split('\n').map(|ln| ln.parse::<Act>())
+        let input = input
+            .split('\n')
+            .map(|ln| ln.parse::<Act>().at(input, ln))
+            .collect::<Result<Vec<_>, _>>()?;
+        aoc::mark_parsed();
+        input
     }};
 }
 
 struct NaiveShip {
-    pos: (isize, isize),
-    dir: Dir,
+    pos: Point<2>,
+    dir: Vector<2>,
 }
 
 impl Ship for NaiveShip {
     fn do_act(&mut self, act: Act) -> DynResult<()> {
         match act {
-            Act::Dir(dir, val) => match dir {
-                Dir::N => self.pos.1 += val,
-                Dir::S => self.pos.1 -= val,
-                Dir::E => self.pos.0 += val,
-                Dir::W => self.pos.0 -= val,
-            },
-            Act::Rotate(degs) => {
-                const DIRS_CLOCKWISE: &[Dir] = &[Dir::N, Dir::E, Dir::S, Dir::W];
-                self.dir = DIRS_CLOCKWISE[(self.dir as usize + degs as usize / 90) % 4]
-            }
-            Act::Forward(val) => self.do_act(Act::Dir(self.dir, val))?,
+            Act::Move(dir, val) => step(&mut self.pos, dir, val)?,
+            Act::Rotate(turns) => self.dir = self.dir.rotate(turns),
+            Act::Forward(val) => step(&mut self.pos, self.dir, val)?,
         }
         Ok(())
     }
 
-    fn manhattan_dist(&self) -> usize {
-        (self.pos.0.abs() + self.pos.1.abs()) as usize
+    fn pos(&self) -> Point<2> {
+        self.pos
     }
 }
 
 struct WaypointShip {
-    pos: (isize, isize),
-    waypoint: (isize, isize),
+    pos: Point<2>,
+    waypoint: Vector<2>,
 }
 
 impl Ship for WaypointShip {
     fn do_act(&mut self, act: Act) -> DynResult<()> {
         match act {
-            Act::Dir(dir, val) => match dir {
-                Dir::N => self.waypoint.1 += val,
-                Dir::S => self.waypoint.1 -= val,
-                Dir::E => self.waypoint.0 += val,
-                Dir::W => self.waypoint.0 -= val,
-            },
-            Act::Rotate(degs) => {
-                let waypoint = self.waypoint;
-                let steps = (degs / 90) % 4;
-                match steps {
-                    0 => {}
-                    1 => (self.waypoint = (waypoint.1, -waypoint.0)),
-                    2 => (self.waypoint = (-waypoint.0, -waypoint.1)),
-                    3 => (self.waypoint = (-waypoint.1, waypoint.0)),
-                    _ => unreachable!(),
+            Act::Move(dir, val) => step(&mut self.waypoint, dir, val)?,
+            Act::Rotate(turns) => {
+                // rotating negates coordinates, which overflows at isize::MIN
+                if self.waypoint.checked_manhattan().is_none() {
+                    return Err("waypoint too far away".into());
                 }
+                self.waypoint = self.waypoint.rotate(turns)
             }
-            Act::Forward(val) => {
-                self.pos.0 += self.waypoint.0 * val;
-                self.pos.1 += self.waypoint.1 * val;
-            }
+            Act::Forward(val) => step(&mut self.pos, self.waypoint, val)?,
         }
         Ok(())
     }
 
-    fn manhattan_dist(&self) -> usize {
-        (self.pos.0.abs() + self.pos.1.abs()) as usize
+    fn pos(&self) -> Point<2> {
+        self.pos
     }
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     let mut ship = NaiveShip {
-        pos: (0, 0),
-        dir: Dir::E,
+        pos: Point::ORIGIN,
+        dir: EAST,
     };
     ship.run(input)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     let mut ship = WaypointShip {
-        pos: (0, 0),
-        waypoint: (10, 1),
+        pos: Point::ORIGIN,
+        waypoint: Point([10, 1]),
     };
     ship.run(input)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate `size` navigation instructions.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| match rng.range(0..7) {
+            i @ 0..=4 => format!("{}{}", b"NSEWF"[i] as char, rng.range(1..100)),
+            i => format!(
+                "{}{}",
+                if i == 5 { 'L' } else { 'R' },
+                rng.choose(&[90, 180, 270])
+            ),
+        })
+        .join("\n")
+}
 
-    const EXAMPLE_1: &str = "
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = "
 F10
 N3
 F7
@@ -158,13 +162,17 @@ R90
 F11
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 25 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -173,6 +181,6 @@ F11
         let expected = { 286 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
@@ -1,32 +1,44 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
         let mut ln = input.split('\n');
-        let time = ln.next().ok_or("missing timestamp")?.parse::<usize>()?;
+        let time = ln.next().unwrap();
+        let time = time.parse::<usize>().at(input, time)?;
         let buses = ln
             .next()
-            .ok_or("missing buses")?
+            .ok_or_at(input, &input[input.len()..], "missing buses")?
             .split(',')
-            .map(|b| b.parse::<usize>().ok());
+            .map(|b| match b {
+                "x" => Ok(None),
+                b => match b.parse::<usize>().at(input, b)? {
+                    0 => Err(InputError::new(input, b, "bus IDs must be non-zero")),
+                    n => Ok(Some(n)),
+                },
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        aoc::mark_parsed();
         (time, buses)
     }};
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let (time, buses) = munge_input!(input);
 
-    let valid_buses = buses.filter_map(|b| b);
+    let valid_buses = buses.into_iter().flatten();
     let (bus, wait_time) = valid_buses
-        .map(|bus| (bus, bus - (time % bus)))
+        // a bus leaving right as we arrive means no waiting at all
+        .map(|bus| (bus, (bus - time % bus) % bus))
         .min_by_key(|&(_, wait_time)| wait_time)
-        .unwrap();
+        .ok_or("no buses in service")?;
 
-    Ok(bus * wait_time)
+    Ok(bus.checked_mul(wait_time).ok_or("answer overflowed")?)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let (_, buses) = munge_input!(input);
 
     // Right, I won't lie, I went down a real rabbit hole thinking there was some
@@ -47,36 +59,109 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
     // And here's a fantastic visual explanation of how it works:
     // https://www.reddit.com/r/adventofcode/comments/kcl7d2/2020_day_13_part_2_buses_in_a_slot_machine/
 
-    let buses = buses.enumerate().filter_map(|(i, b)| Some((i, b?)));
+    let buses = (buses.into_iter().enumerate()).filter_map(|(i, b)| Some((i, b?)));
 
     let mut ans = 0;
     let mut lcd = 1; // least common divisor
     for (offset, bus) in buses {
-        while (ans + offset) % bus != 0 {
-            ans += lcd;
-        }
-        lcd *= bus;
+        // rather than stepping by `lcd` until the bus lines up (which takes
+        // forever with huge bus IDs), figure out how many steps it'll take
+        let need = (bus - (ans % bus + offset % bus) % bus) % bus;
+        let steps = solve_congruence(lcd % bus, need, bus).ok_or("buses never line up")?;
+        ans = (steps.checked_mul(lcd))
+            .and_then(|n| n.checked_add(ans))
+            .ok_or("answer overflowed")?;
+        lcd = (lcd / lcd.gcd(bus))
+            .checked_mul(bus)
+            .ok_or("answer overflowed")?;
     }
 
     Ok(ans)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Find the smallest `k` such that `a * k = b (mod m)`, if there is one.
+fn solve_congruence(a: usize, b: usize, m: usize) -> Option<usize> {
+    let g = a.gcd(m);
+    if b % g != 0 {
+        return None;
+    }
+    let (a, b, m) = (a / g, b / g, m / g);
+    if m == 1 {
+        return Some(0);
+    }
+
+    // extended euclidean algorithm, to find the inverse of `a` (mod `m`)
+    let (mut r0, mut r1) = (a as i128, m as i128);
+    let (mut s0, mut s1) = (1i128, 0i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        let (r, s) = (r0 - q * r1, s0 - q * s1);
+        r0 = r1;
+        r1 = r;
+        s0 = s1;
+        s1 = s;
+    }
+    let inv = s0.rem_euclid(m as i128) as u128;
+
+    Some((inv * b as u128 % m as u128) as usize)
+}
+
+/// Generate a bus schedule with `size` entries, most of which are out of
+/// service. Bus IDs are distinct primes, so part 2 always has an answer.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+
+    let mut primes = (7..1000)
+        .filter(|&n| (2..).take_while(|d| d * d <= n).all(|d| n % d != 0))
+        .collect::<Vec<usize>>();
+    rng.shuffle(&mut primes);
+
+    // keep the product of the IDs (i.e: the upper bound on part 2's answer)
+    // well within a u64
+    let mut product = 1;
+    let mut buses = Vec::new();
+    for bus in primes {
+        if buses.len() == size.min(9) {
+            break;
+        }
+        if product * bus <= 1_000_000_000_000_000 {
+            product *= bus;
+            buses.push(bus);
+        }
+    }
+
+    // the first entry is always a bus
+    let mut slots = (1..size).collect::<Vec<_>>();
+    rng.shuffle(&mut slots);
+    let mut schedule = vec!["x".to_string(); size];
+    for (slot, bus) in core::iter::once(0).chain(slots).zip(buses) {
+        schedule[slot] = bus.to_string();
+    }
+
+    format!("{}\n{}", rng.range(100_000..1_000_000), schedule.join(","))
+}
 
-    const EXAMPLE_1: &str = "
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = "
 939
 7,13,x,x,59,x,31,19
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::prop;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 295 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -85,6 +170,58 @@ mod tests {
         let expected = { 1068781 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }
+
+    /// Like `generate`, but with small bus IDs, so answers can be found by
+    /// checking every minute.
+    fn generate_small(rng: &mut Rng, size: usize) -> String {
+        let mut primes = vec![2, 3, 5, 7, 11, 13, 17, 19, 23];
+        rng.shuffle(&mut primes);
+        let buses = &primes[..rng.range(1..5)];
+
+        let mut schedule = vec!["x".to_string(); buses.len() + size];
+        let mut slots = (1..schedule.len()).collect::<Vec<_>>();
+        rng.shuffle(&mut slots);
+        for (slot, bus) in core::iter::once(0).chain(slots).zip(buses) {
+            schedule[slot] = bus.to_string();
+        }
+
+        format!("{}\n{}", rng.range(0..1000), schedule.join(","))
+    }
+
+    fn reference_buses(input: &str) -> (usize, Vec<(usize, usize)>) {
+        let (time, schedule) = input.split_once('\n').unwrap();
+        let buses = schedule
+            .split(',')
+            .enumerate()
+            .filter(|&(_, bus)| bus != "x")
+            .map(|(i, bus)| (i, bus.parse().unwrap()))
+            .collect();
+        (time.parse().unwrap(), buses)
+    }
+
+    fn reference_q1(input: &str) -> usize {
+        let (time, buses) = reference_buses(input);
+        (time..)
+            .find_map(|t| {
+                let (_, bus) = buses.iter().find(|(_, bus)| t % bus == 0)?;
+                Some(bus * (t - time))
+            })
+            .unwrap()
+    }
+
+    fn reference_q2(input: &str) -> usize {
+        let (_, buses) = reference_buses(input);
+        (0..)
+            .find(|t| buses.iter().all(|(i, bus)| (t + i) % bus == 0))
+            .unwrap()
+    }
+
+    #[test]
+    fn prop_matches_reference() {
+        let opts = Options::default();
+        prop::check(20, generate_small, |i| q1(i, &opts), reference_q1);
+        prop::check(20, generate_small, |i| q2(i, &opts), reference_q2);
     }
 }
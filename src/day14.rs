@@ -1,5 +1,7 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 struct Mask {
     /// positions of 'X'
     float: u64,
@@ -14,11 +16,16 @@ enum Instr {
     Mem { addr: u64, val: u64 },
 }
 
+/// Masks with more floating bits than this would write to an unreasonable
+/// number of addresses.
+const MAX_FLOATING: u32 = 16;
+
 fn parse_mask(s: &str) -> DynResult<Mask> {
-    s.split(" = ")
-        .nth(1)
-        .ok_or("missing bitmask")?
-        .chars()
+    let mask = s.split(" = ").nth(1).ok_or("missing bitmask")?;
+    if mask.len() > 36 {
+        return Err("bitmask must be at most 36 bits".into());
+    }
+    mask.chars()
         .try_fold(
             Mask {
                 float: 0,
@@ -64,19 +71,23 @@ fn parse_mem(s: &str) -> DynResult<(u64, u64)> {
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        let instrs = input.split('\n').map(|ln| -> DynResult<_> {
-            if ln.starts_with("mask") {
-                Ok(Instr::Mask(parse_mask(ln)?))
-            } else {
-                let (addr, val) = parse_mem(ln)?;
-                Ok(Instr::Mem { addr, val })
-            }
-        });
+        let instrs = input
+            .split('\n')
+            .map(|ln| -> Result<_, InputError> {
+                if ln.starts_with("mask") {
+                    Ok(Instr::Mask(parse_mask(ln).at(input, ln)?))
+                } else {
+                    let (addr, val) = parse_mem(ln).at(input, ln)?;
+                    Ok(Instr::Mem { addr, val })
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        aoc::mark_parsed();
         instrs
     }};
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<u64> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<u64> {
     let input = munge_input!(input);
 
     let mut mask = Mask {
@@ -87,7 +98,6 @@ pub fn q1(input: &str, _args: &[&str]) -> DynResult<u64> {
     let mut mem: HashMap<u64, u64> = HashMap::new();
 
     for instr in input {
-        let instr = instr?;
         match instr {
             Instr::Mask(new_mask) => mask = new_mask,
             Instr::Mem { addr, val } => {
@@ -96,11 +106,14 @@ pub fn q1(input: &str, _args: &[&str]) -> DynResult<u64> {
         }
     }
 
-    let memsum = mem.into_iter().map(|(_, v)| v).sum();
+    let memsum = mem
+        .values()
+        .try_fold(0u64, |a, &v| a.checked_add(v))
+        .ok_or("answer overflowed")?;
     Ok(memsum)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<u64> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<u64> {
     let input = munge_input!(input);
 
     let mut mask = Mask {
@@ -111,10 +124,15 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<u64> {
     let mut mem: HashMap<u64, u64> = HashMap::new();
 
     for instr in input {
-        let instr = instr?;
         match instr {
             Instr::Mask(new_mask) => mask = new_mask,
             Instr::Mem { addr, val } => {
+                if mask.float.count_ones() > MAX_FLOATING {
+                    return Err(
+                        format!("bitmask has more than {} floating bits", MAX_FLOATING).into(),
+                    );
+                }
+
                 let set_bits = {
                     let mut v = Vec::new(); // could be a fixed-size array
                     let mut f = mask.float;
@@ -137,43 +155,77 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<u64> {
         }
     }
 
-    let memsum = mem.into_iter().map(|(_, v)| v).sum();
+    let memsum = mem
+        .values()
+        .try_fold(0u64, |a, &v| a.checked_add(v))
+        .ok_or("answer overflowed")?;
     Ok(memsum)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate a program with (at least) `size` memory writes, grouped under
+/// masks with at most 9 floating bits.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut lines = Vec::new();
+    let mut writes = 0;
+    while writes < size.max(1) {
+        let mut bits = (0..36).collect::<Vec<_>>();
+        rng.shuffle(&mut bits);
+        let floating = &bits[..rng.range(0..10)];
+        let mask = (0..36)
+            .rev()
+            .map(|bit| match floating.contains(&bit) {
+                true => 'X',
+                false => *rng.choose(&['0', '1']),
+            })
+            .collect::<String>();
+        lines.push(format!("mask = {}", mask));
+
+        for _ in 0..rng.range(1..6) {
+            let (addr, val) = (rng.range(0..1 << 16), rng.range(0..1 << 36));
+            lines.push(format!("mem[{}] = {}", addr, val));
+            writes += 1;
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1, EXAMPLE_2];
 
-    const EXAMPLE_1: &str = "
+const EXAMPLE_1: &str = "
 mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
 mem[8] = 11
 mem[7] = 101
 mem[8] = 0
 ";
 
+const EXAMPLE_2: &str = "
+mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 165 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
-    const EXAMPLE_2: &str = "
-mask = 000000000000000000000000000000X1001X
-mem[42] = 100
-mask = 00000000000000000000000000000000X0XX
-mem[26] = 1
-";
-
     #[test]
     fn q2_e1() {
         let input = EXAMPLE_2;
         let expected = { 208 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
@@ -1,12 +1,16 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input
+        let input = input
             .split(',')
-            .map(|s| s.parse::<usize>())
-            .collect::<Result<Vec<_>, _>>()?
+            .map(|s| s.parse::<usize>().at(input, s))
+            .collect::<Result<Vec<_>, _>>()?;
+        aoc::mark_parsed();
+        input
     }};
 }
 
@@ -46,24 +50,37 @@ fn solve(init: &[usize], end_turn: usize) -> usize {
     prev_n
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     Ok(solve(&input, 2020))
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     eprintln!("It'll run faster in --release!");
     Ok(solve(&input, 30000000))
 }
 
+/// Generate `size` (at most 10) distinct starting numbers.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut nums = (0..20).collect::<Vec<_>>();
+    rng.shuffle(&mut nums);
+    nums.truncate(size.clamp(1, 10));
+    nums.iter().join(",")
+}
+
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = "
+0,3,6
+";
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const EXAMPLE_1: &str = "
-0,3,6
-";
+    use crate::util::prop;
 
     #[test]
     fn q1_e1() {
@@ -71,6 +88,28 @@ mod tests {
         let expected = { 436 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }
+
+    fn reference(input: &str, turns: usize) -> usize {
+        let mut spoken = input
+            .split(',')
+            .map(|n| n.parse().unwrap())
+            .collect::<Vec<usize>>();
+        while spoken.len() < turns {
+            let (last, before) = spoken.split_last().unwrap();
+            let next = match before.iter().rposition(|n| n == last) {
+                Some(i) => before.len() - i,
+                None => 0,
+            };
+            spoken.push(next);
+        }
+        spoken[turns - 1]
+    }
+
+    #[test]
+    fn prop_matches_reference() {
+        let opts = Options::default();
+        prop::check(10, generate, |i| q1(i, &opts), |i| reference(i, 2020));
     }
 }
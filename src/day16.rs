@@ -1,5 +1,7 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 type Range = (usize, usize);
 
 macro_rules! munge_input {
@@ -7,56 +9,63 @@ macro_rules! munge_input {
         let input = $input;
         let mut sections = input.split("\n\n");
 
+        let parse_range = |range: &str| -> Result<Range, InputError> {
+            let (lo, hi) = range
+                .split_once('-')
+                .ok_or_at(input, range, "expected range (e.g: 1-3)")?;
+            Ok((lo.parse().at(input, lo)?, hi.parse().at(input, hi)?))
+        };
+
         let fields = sections
             .next()
-            .ok_or("missing fields section")?
+            .unwrap()
             .split('\n')
-            .map(|ln| -> DynResult<_> {
-                let mut chunks = ln.split(": ");
-                let name = chunks.next().unwrap();
-                let mut ranges = chunks
-                    .next()
-                    .ok_or("missing field ranges")?
-                    .split(" or ")
-                    .map(|range| -> DynResult<_> {
-                        let mut nums = range.split('-').map(|n| n.parse::<usize>());
-                        Ok((
-                            nums.next().ok_or("missing num in range")??,
-                            nums.next().ok_or("missing num in range")??,
-                        ))
-                    });
-                Ok((
-                    name,
-                    ranges.next().ok_or("missing first range")??,
-                    ranges.next().ok_or("missing second range")??,
-                ))
+            .map(|ln| -> Result<_, InputError> {
+                let (name, ranges) = ln
+                    .split_once(": ")
+                    .ok_or_at(input, ln, "missing field ranges")?;
+                let (r1, r2) = ranges
+                    .split_once(" or ")
+                    .ok_or_at(input, ranges, "expected two ranges (e.g: 1-3 or 5-7)")?;
+                Ok((name, parse_range(r1)?, parse_range(r2)?))
             })
-            .collect::<DynResult<Vec<(&str, Range, Range)>>>()?;
+            .collect::<Result<Vec<(&str, Range, Range)>, _>>()?;
+
+        let parse_ticket = |ticket: &str| {
+            let vals = ticket
+                .split(',')
+                .map(|n| n.parse::<usize>().at(input, n))
+                .collect::<Result<Vec<_>, _>>()?;
+            if vals.len() != fields.len() {
+                return Err(InputError::new(
+                    input,
+                    ticket,
+                    format!("expected ticket to have {} fields", fields.len()),
+                ));
+            }
+            Ok(vals)
+        };
 
-        let my_ticket: Vec<usize> = sections
-            .next()
-            .ok_or("missing my ticket section")?
-            .split('\n')
-            .skip(1) // "your ticket:" header
+        let my_ticket_section = sections
             .next()
-            .ok_or("missing my ticket")?
-            .split(',')
-            .map(|n| n.parse::<usize>())
-            .collect::<Result<Vec<_>, _>>()?;
+            .ok_or_at(input, &input[input.len()..], "missing my ticket section")?;
+        let my_ticket: Vec<usize> = parse_ticket(
+            my_ticket_section
+                .split('\n')
+                .skip(1) // "your ticket:" header
+                .next()
+                .ok_or_at(input, my_ticket_section, "missing my ticket")?,
+        )?;
 
         let tickets: Vec<Vec<usize>> = sections
             .next()
-            .ok_or("missing nearby tickets section")?
+            .ok_or_at(input, &input[input.len()..], "missing nearby tickets section")?
             .split('\n')
             .skip(1) // "nearby tickets:" header
-            .map(|ticket| {
-                ticket
-                    .split(',')
-                    .map(|n| n.parse::<usize>())
-                    .collect::<Result<Vec<_>, _>>()
-            })
+            .map(parse_ticket)
             .collect::<Result<Vec<_>, _>>()?;
 
+        aoc::mark_parsed();
         (fields, my_ticket, tickets)
     }};
 }
@@ -64,24 +73,24 @@ macro_rules! munge_input {
 fn filter_invalid(
     fields: &[(&str, Range, Range)],
     tickets: Vec<Vec<usize>>,
-) -> (usize, Vec<Vec<usize>>) {
-    // this is a valid approach since the AoC output limits ranges for numbers
-    // between 0 and 1000.
-    let any_valid_map = fields
-        .iter()
-        .flat_map(|&(_, r1, r2)| Some(r1).into_iter().chain(Some(r2)))
-        .map(|(start, end)| start..=end)
-        .flatten()
-        .collect::<HashSet<usize>>();
+) -> DynResult<(usize, Vec<Vec<usize>>)> {
+    // I used to build a set of every valid number here, which was fine for AoC
+    // inputs (where ranges are all between 0 and 1000), but not so much for
+    // something like `0-18446744073709551615`...
+    let any_valid = |val: &usize| {
+        fields.iter().any(|&(_, r1, r2)| {
+            (r1.0..=r1.1).contains(val) || (r2.0..=r2.1).contains(val)
+        })
+    };
 
     let mut valid = Vec::new();
-    let mut error_rate = 0;
+    let mut error_rate = 0usize;
 
     for ticket in tickets {
         let mut is_valid = true;
         for val in &ticket {
-            if !any_valid_map.contains(&val) {
-                error_rate += val;
+            if !any_valid(val) {
+                error_rate = error_rate.checked_add(*val).ok_or("error rate overflowed")?;
                 is_valid = false;
             }
         }
@@ -90,28 +99,28 @@ fn filter_invalid(
         }
     }
 
-    (error_rate, valid)
+    Ok((error_rate, valid))
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let (fields, _my_ticket, tickets) = munge_input!(input);
 
-    let (error_rate, _) = filter_invalid(&fields, tickets);
+    let (error_rate, _) = filter_invalid(&fields, tickets)?;
 
     Ok(error_rate)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let (fields, my_ticket, tickets) = munge_input!(input);
 
-    let (_, tickets) = filter_invalid(&fields, tickets);
+    let (_, tickets) = filter_invalid(&fields, tickets)?;
 
     // associate field (index) with valid columns in tickets
     let mut guess_map: HashMap<usize, Vec<usize>> = HashMap::new();
     for (field_idx, (_, r1, r2)) in fields.iter().enumerate() {
         let (r1, r2) = (r1.0..=r1.1, r2.0..=r2.1);
 
-        'col: for col in 0..tickets[0].len() {
+        'col: for col in 0..fields.len() {
             for val in tickets.iter().map(|ticket| ticket[col]) {
                 if !r1.contains(&val) && !r2.contains(&val) {
                     continue 'col;
@@ -196,7 +205,7 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
             if !fixed_map.contains_key(&field) {
                 // recurse on the guess
                 let mut fixed_map = fixed_map.clone();
-                fixed_map.insert(field, col).unwrap();
+                fixed_map.insert(field, col);
                 let guess_map = fixup_guess_map(guess_map.clone(), (field, col));
 
                 if let Some(soln) = solve(guess_map, fixed_map) {
@@ -216,17 +225,106 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
         .iter()
         .enumerate()
         .filter(|(_, (name, _, _))| name.starts_with("departure"))
-        .map(|(i, _)| my_ticket[solution[&i]])
-        .product();
+        .map(|(i, _)| solution.get(&i).map(|&col| my_ticket[col]))
+        .try_fold(1usize, |a, v| a.checked_mul(v?))
+        .ok_or("could not find a solution")?;
 
     Ok(soln)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate notes with 20 fields and `size` nearby tickets, where the order of
+/// the fields can be deduced purely by elimination.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    const NAMES: &[&str] = &[
+        "departure location",
+        "departure station",
+        "departure platform",
+        "departure track",
+        "departure date",
+        "departure time",
+        "arrival location",
+        "arrival station",
+        "arrival platform",
+        "arrival track",
+        "class",
+        "duration",
+        "price",
+        "route",
+        "row",
+        "seat",
+        "train",
+        "type",
+        "wagon",
+        "zone",
+    ];
+    const N: usize = NAMES.len();
+    // the values in column `cols[k]` all fall within the k'th band
+    const BASE: usize = 100;
+    const BAND: usize = 40;
+    let band = |k: usize| BASE + k * BAND..BASE + (k + 1) * BAND;
+
+    // the k'th field accepts the first k + 1 bands, (and the lower half of some
+    // later band, as a red herring), so fields can be assigned columns in order
+    let mut cols = (0..N).collect::<Vec<_>>();
+    rng.shuffle(&mut cols);
+    let mut names = NAMES.to_vec();
+    rng.shuffle(&mut names);
+
+    let mut fields = (0..N)
+        .map(|k| {
+            let r2 = match k + 1 < N {
+                true => band(rng.range(k + 1..N)).start,
+                false => band(N).start,
+            };
+            format!(
+                "{}: {}-{} or {}-{}",
+                names[k],
+                BASE,
+                band(k).end - 1,
+                r2,
+                r2 + BAND / 2 - 1
+            )
+        })
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut fields);
+
+    let ticket = |rng: &mut Rng, upper_half: bool| {
+        let mut vals = vec![0; N];
+        for (k, &col) in cols.iter().enumerate() {
+            let range = band(k);
+            vals[col] = match upper_half {
+                true => rng.range(range.start + BAND / 2..range.end),
+                false => rng.range(range),
+            };
+        }
+        vals
+    };
+
+    let mine = ticket(rng, false);
+    // rule out the red herrings by making sure there's a value in the upper
+    // half of every band
+    let mut nearby = vec![ticket(rng, true)];
+    while nearby.len() < size.max(1) {
+        let mut vals = ticket(rng, false);
+        if rng.chance(1, 4) {
+            vals[rng.range(0..N)] = rng.range(1..BASE);
+        }
+        nearby.push(vals);
+    }
+    rng.shuffle(&mut nearby);
+
+    format!(
+        "{}\n\nyour ticket:\n{}\n\nnearby tickets:\n{}",
+        fields.join("\n"),
+        mine.iter().join(","),
+        nearby.iter().map(|t| t.iter().join(",")).join("\n")
+    )
+}
 
-    const EXAMPLE_1: &str = "
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1, EXAMPLE_2];
+
+const EXAMPLE_1: &str = "
 class: 1-3 or 5-7
 row: 6-11 or 33-44
 seat: 13-40 or 45-50
@@ -241,16 +339,7 @@ nearby tickets:
 38,6,12
 ";
 
-    #[test]
-    fn q1_e1() {
-        let input = EXAMPLE_1;
-        let expected = { 71 };
-        let q = q1;
-
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
-    }
-
-    const EXAMPLE_2: &str = "
+const EXAMPLE_2: &str = "
 departure class: 0-1 or 4-19
 departure row: 0-5 or 8-19
 seat: 0-13 or 16-19
@@ -264,12 +353,25 @@ nearby tickets:
 5,14,9
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn q1_e1() {
+        let input = EXAMPLE_1;
+        let expected = { 71 };
+        let q = q1;
+
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }
+
     #[test]
     fn q2_e1() {
         let input = EXAMPLE_2;
         let expected = { 11 * 12 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
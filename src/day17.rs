@@ -1,43 +1,45 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        let mut map = HashSet::new();
-        for (y, ln) in input.split('\n').enumerate() {
-            for (x, c) in ln.chars().enumerate() {
-                if c == '#' {
-                    map.insert((x as isize, y as isize));
-                }
-            }
-        }
-        map
+        let slice = Grid::parse(input, |c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err("expected '.' or '#'"),
+        })?;
+        aoc::mark_parsed();
+        slice
     }};
 }
 
-fn solve<const DIMS: usize>(input: HashSet<(isize, isize)>, iters: usize) -> usize {
-    let mut curr = input
-        .into_iter()
-        .map(|(x, y)| {
-            let mut a = [0; DIMS];
-            a[0] = x;
-            a[1] = y;
-            a
+fn solve<const DIMS: usize>(slice: &Grid<bool>, iters: usize) -> usize {
+    let mut curr = slice
+        .enumerate()
+        .filter(|(_, &active)| active)
+        .map(|((x, y), _)| {
+            let mut p = Point::<DIMS>::ORIGIN;
+            p.0[0] = x as isize;
+            p.0[1] = y as isize;
+            p
         })
         .collect::<HashSet<_>>();
     let mut next = HashSet::new();
 
-    let deltas = [-1..=1; DIMS]
-        .cartesian_product()
-        .filter(|c| *c != [0; DIMS]);
-
     for _ in 0..iters {
+        // nothing can come back to life once everything is dead
+        if curr.is_empty() {
+            break;
+        }
+
         // calculate bounds
         let bounds = (0..DIMS)
             .map(|d| {
                 let (min, max) = curr
                     .iter()
-                    .map(|cord| cord[d])
+                    .map(|cord| cord.0[d])
                     .minmax()
                     .into_option()
                     .unwrap();
@@ -46,17 +48,8 @@ fn solve<const DIMS: usize>(input: HashSet<(isize, isize)>, iters: usize) -> usi
             .take_array() // uses the `iter_to_array` crate
             .unwrap();
 
-        for cord in bounds.cartesian_product() {
-            let neighbors = deltas
-                .clone()
-                .filter(|deltas| {
-                    let mut p = [0; DIMS];
-                    for ((val, base), delta) in p.iter_mut().zip(cord.iter()).zip(deltas.iter()) {
-                        *val = base + delta;
-                    }
-                    curr.contains(&p)
-                })
-                .count();
+        for cord in bounds.cartesian_product().map(Point) {
+            let neighbors = cord.neighbors().filter(|p| curr.contains(p)).count();
 
             if matches!(
                 (curr.contains(&cord), neighbors),
@@ -73,33 +66,52 @@ fn solve<const DIMS: usize>(input: HashSet<(isize, isize)>, iters: usize) -> usi
     curr.len()
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
-    Ok(solve::<3>(input, 6))
+    Ok(solve::<3>(&input, 6))
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
-    Ok(solve::<4>(input, 6))
+    Ok(solve::<4>(&input, 6))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate a `size` x `size` (at most 12 x 12) initial slice.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.clamp(1, 12);
+    let mut grid = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.chance(1, 3) { '#' } else { '.' })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    // there has to be at least one active cube
+    grid[rng.range(0..size)][rng.range(0..size)] = '#';
+
+    grid.iter().map(|row| row.iter().collect::<String>()).join("\n")
+}
+
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
 
-    const EXAMPLE_1: &str = "
+const EXAMPLE_1: &str = "
 .#.
 ..#
 ###
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 112 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -108,6 +120,6 @@ mod tests {
         let expected = { 848 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
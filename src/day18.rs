@@ -1,5 +1,7 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 // CONTENT WARNING: ABSOLUTELY TRASH CODE
 //
 // Challenge: can I slap together a solution without writing a "proper" parser?
@@ -16,48 +18,74 @@ use crate::prelude::*;
 //
 // anyways, yeah, fuck this problem, and fuck this code.
 
-fn solve_expr(s: &[u8], with_precedence: bool) -> (u64, usize) {
+/// Parenthesized expressions nested deeper than this are rejected, instead of
+/// overflowing the stack.
+const MAX_DEPTH: usize = 256;
+
+/// Returns the value of the expression, along with the index of the `)` which
+/// terminated it (if any).
+fn solve_expr(
+    s: &[u8],
+    with_precedence: bool,
+    depth: usize,
+) -> DynResult<(u64, Option<usize>)> {
     // eprintln!("solving {:?}", String::from_utf8_lossy(s));
 
+    if depth > MAX_DEPTH {
+        return Err("expression is nested too deeply".into());
+    }
+
     let mut vals = Vec::new();
     let mut ops = Vec::new();
 
     let mut chars = s.iter().copied().enumerate();
     let mut final_i = None;
     while let Some((i, c)) = chars.next() {
+        // values and operators have to alternate
+        let expect_val = vals.len() == ops.len();
         match c {
             b' ' => continue,
-            b'+' | b'*' => ops.push(c as char),
-            b'0'..=b'9' => vals.push((c - b'0') as u64),
-            b'(' => {
-                let (val, end_i) = solve_expr(&s[(i + 1)..], with_precedence);
+            b'+' | b'*' if !expect_val => ops.push(c as char),
+            b'0'..=b'9' if expect_val => vals.push((c - b'0') as u64),
+            b'(' if expect_val => {
+                let (val, end_i) = solve_expr(&s[(i + 1)..], with_precedence, depth + 1)?;
                 vals.push(val);
 
-                let end_i = end_i + i;
+                let end_i = end_i.ok_or("unbalanced parentheses")? + i + 1;
                 // eprintln!("skipping until {}", end_i);
-                while !matches!(chars.next(), Some((i, _)) if i == end_i) {}
-                chars.next();
-                // eprintln!(
-                //     "remaining: {}",
-                //     String::from_utf8(chars.clone().map(|(_, c)|
-                // c).collect::<Vec<u8>>())
-                //         .unwrap_or_default()
-                // )
+                chars.find(|&(i, _)| i == end_i);
             }
             b')' => {
                 final_i = Some(i);
                 break;
             }
-            _ => panic!("invalid input"),
+            b'+' | b'*' | b'0'..=b'9' | b'(' => {
+                return Err("expected values and operators to alternate".into())
+            }
+            _ => return Err("invalid character in expression".into()),
         }
     }
 
+    if vals.len() != ops.len() + 1 {
+        return Err("incomplete expression".into());
+    }
+
     vals.reverse();
     ops.reverse();
 
     // eprintln!("vals: {:?}", vals);
     // eprintln!("ops: {:?}", ops);
 
+    let calc = |a: u64, op: char, b: u64| -> DynResult<u64> {
+        // eprintln!("calc: {} {} {}", a, op, b);
+        let val = match op {
+            '+' => a.checked_add(b),
+            '*' => a.checked_mul(b),
+            _ => unreachable!(),
+        };
+        Ok(val.ok_or("expression overflowed")?)
+    };
+
     if with_precedence {
         // eval all '+' first
         while let Some(pos) = ops.iter().position(|op| *op == '+') {
@@ -65,73 +93,94 @@ fn solve_expr(s: &[u8], with_precedence: bool) -> (u64, usize) {
 
             let a = vals.remove(pos);
             let b = vals.remove(pos);
-            // eprintln!("calc: {} {} {}", a, op, b);
-
-            vals.insert(
-                pos,
-                match op {
-                    '+' => a + b,
-                    '*' => a * b,
-                    _ => unreachable!(),
-                },
-            );
+            vals.insert(pos, calc(a, op, b)?);
         }
     }
 
     while let Some(op) = ops.pop() {
         let a = vals.pop().unwrap();
         let b = vals.pop().unwrap();
-
-        // eprintln!("calc: {} {} {}", a, op, b);
-
-        vals.push(match op {
-            '+' => a + b,
-            '*' => a * b,
-            _ => unreachable!(),
-        });
+        vals.push(calc(a, op, b)?);
     }
 
-    // eprintln!("returned {}, {}\n", vals[0], final_i.unwrap_or_default());
-
-    assert!(vals.len() == 1);
+    // eprintln!("returned {}, {:?}\n", vals[0], final_i);
 
-    (vals[0], final_i.unwrap_or_default())
+    Ok((vals[0], final_i))
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
-    let sum = input
-        .split('\n')
-        .map(|ln| solve_expr(ln.as_bytes(), false).0)
-        .sum::<u64>();
+fn solve(input: &str, with_precedence: bool) -> DynResult<usize> {
+    let mut sum = 0u64;
+    for ln in input.split('\n') {
+        let (val, end_i) = solve_expr(ln.as_bytes(), with_precedence, 0).at(input, ln)?;
+        if let Some(end_i) = end_i {
+            return Err(InputError::new(input, &ln[end_i..], "unbalanced parentheses").into());
+        }
+        sum = sum.checked_add(val).ok_or("answer overflowed")?;
+    }
     Ok(sum as usize)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
-    let sum = input
-        .split('\n')
-        .map(|ln| solve_expr(ln.as_bytes(), true).0)
-        .sum::<u64>();
-    Ok(sum as usize)
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
+    solve(input, false)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
+    solve(input, true)
+}
+
+/// Generate `size` expressions, each using at most 12 numbers, so that every
+/// answer fits in a `u64`.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    /// Generate an expression using at most `budget` (>= 2) numbers, deducting
+    /// however many numbers were used.
+    fn expr(rng: &mut Rng, budget: &mut usize, depth: usize) -> String {
+        let mut s = String::new();
+        let terms = rng.range(2..(*budget).min(4) + 1);
+        for i in 0..terms {
+            if i != 0 {
+                s.push_str(if rng.chance(1, 2) { " + " } else { " * " });
+            }
+
+            // leave at least one number for each of the remaining terms
+            let available = *budget - (terms - i - 1);
+            if depth < 3 && available >= 2 && rng.chance(1, 3) {
+                let mut inner = available;
+                s.push_str(&format!("({})", expr(rng, &mut inner, depth + 1)));
+                *budget -= available - inner;
+            } else {
+                s.push((b'1' + rng.range(0..9) as u8) as char);
+                *budget -= 1;
+            }
+        }
+        s
+    }
+
+    (0..size.max(1)).map(|_| expr(rng, &mut 12, 0)).join("\n")
+}
 
-    const EXAMPLE_1: &str = "
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = "
 2 * 3 + (4 * 5)
 5 + (8 * 3 + 9 + 3 * 4 * 3)
 5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))
 ((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::prop;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 26 + 437 + 12240 + 13632 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -140,6 +189,68 @@ mod tests {
         let expected = { 46 + 1445 + 669060 + 23340 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }
+
+    /// A "proper" recursive descent parser, where `+` optionally binds tighter
+    /// than `*`.
+    fn reference(input: &str, add_first: bool) -> usize {
+        // tokens are popped off the back
+        fn term(toks: &mut Vec<char>, add_first: bool) -> usize {
+            match toks.pop().unwrap() {
+                '(' => {
+                    let val = expr(toks, add_first);
+                    assert_eq!(toks.pop(), Some(')'));
+                    val
+                }
+                c => c.to_digit(10).unwrap() as usize,
+            }
+        }
+
+        fn sum(toks: &mut Vec<char>, add_first: bool) -> usize {
+            let mut val = term(toks, add_first);
+            while toks.last() == Some(&'+') {
+                toks.pop();
+                val += term(toks, add_first);
+            }
+            val
+        }
+
+        fn expr(toks: &mut Vec<char>, add_first: bool) -> usize {
+            if add_first {
+                let mut val = sum(toks, add_first);
+                while toks.last() == Some(&'*') {
+                    toks.pop();
+                    val *= sum(toks, add_first);
+                }
+                return val;
+            }
+
+            let mut val = term(toks, add_first);
+            while let Some(&op) = toks.last() {
+                if op == ')' {
+                    break;
+                }
+                toks.pop();
+                let rhs = term(toks, add_first);
+                val = if op == '+' { val + rhs } else { val * rhs };
+            }
+            val
+        }
+
+        input
+            .split('\n')
+            .map(|ln| {
+                let mut toks = ln.chars().filter(|&c| c != ' ').rev().collect();
+                expr(&mut toks, add_first)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn prop_matches_reference() {
+        let opts = Options::default();
+        prop::check(20, generate, |i| q1(i, &opts), |i| reference(i, false));
+        prop::check(20, generate, |i| q2(i, &opts), |i| reference(i, true));
     }
 }
@@ -1,5 +1,7 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 #[derive(Debug)]
 enum Rule<'a> {
     Terminal(&'a str),
@@ -12,43 +14,56 @@ macro_rules! munge_input {
         let mut sections = input.split("\n\n");
         let rules = sections
             .next()
-            .ok_or("missing rules")?
+            .unwrap()
             .split('\n')
-            .map(|ln| -> DynResult<_> {
-                let mut ln = ln.split(": ");
-                let key = ln.next().unwrap().parse::<usize>()?;
-                let val = ln.next().ok_or("missing rule rhs")?;
+            .map(|ln| -> Result<_, InputError> {
+                let (key, val) = ln.split_once(": ").ok_or_at(input, ln, "missing rule rhs")?;
+                let key = key.parse::<usize>().at(input, key)?;
                 let rules = val
                     .split(" | ")
-                    .map(|rule| -> DynResult<_> {
+                    .map(|rule| -> Result<_, InputError> {
                         let rule = match rule.strip_prefix('"') {
-                            Some(rest) => {
-                                Rule::Terminal(rest.strip_suffix('"').ok_or("malformed terminal")?)
-                            }
+                            Some(rest) => Rule::Terminal(
+                                rest.strip_suffix('"')
+                                    .ok_or_at(input, rule, "malformed terminal")?,
+                            ),
                             None => Rule::Seq(
                                 rule.split(' ')
-                                    .map(|rule| rule.parse::<usize>())
+                                    .map(|n| n.parse::<usize>().at(input, n))
                                     .collect::<Result<Vec<_>, _>>()?,
                             ),
                         };
                         Ok(rule)
                     })
-                    .collect::<DynResult<Vec<_>>>()?;
+                    .collect::<Result<Vec<_>, _>>()?;
                 Ok((key, rules))
             })
-            .collect::<DynResult<HashMap<_, _>>>()?;
-        let msgs = sections.next().ok_or("missing messages")?.split('\n');
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let msgs = sections
+            .next()
+            .ok_or_at(input, &input[input.len()..], "missing messages")?
+            .split('\n')
+            .collect::<Vec<_>>();
 
+        aoc::mark_parsed();
         (rules, msgs)
     }};
 }
 
+/// Rules which recurse deeper than this (e.g: left-recursive rules, which never
+/// consume any input) are rejected, instead of overflowing the stack.
+const MAX_DEPTH: usize = 1000;
+
 fn validate_msg<'a>(
     msg: &'a str,
     rules: &HashMap<usize, Vec<Rule<'a>>>,
     rule: usize,
     indent: usize,
-) -> Option<&'a str> {
+) -> DynResult<Option<&'a str>> {
+    if indent > MAX_DEPTH {
+        return Err("rules recurse too deeply".into());
+    }
+
     let indent_s = "    ".repeat(indent);
 
     macro_rules! log {
@@ -58,7 +73,9 @@ fn validate_msg<'a>(
     }
 
     log!("validate_msg({} : {:?})", rule, msg);
-    let options = rules.get(&rule).unwrap();
+    let options = rules
+        .get(&rule)
+        .ok_or_else(|| format!("missing rule {}", rule))?;
 
     for rule in options {
         let remaining = match rule {
@@ -67,7 +84,7 @@ fn validate_msg<'a>(
                 let mut msg = Some(msg);
                 for rule in seq {
                     if let Some(inner_msg) = msg {
-                        msg = validate_msg(inner_msg, rules, *rule, indent + 1);
+                        msg = validate_msg(inner_msg, rules, *rule, indent + 1)?;
                     } else {
                         break;
                     }
@@ -78,37 +95,134 @@ fn validate_msg<'a>(
 
         match remaining {
             None => continue,              // this branch hard-failed,
-            Some(msg) => return Some(msg), // cool, this came through fine
+            Some(msg) => return Ok(Some(msg)), // cool, this came through fine
         }
     }
 
-    None
+    Ok(None)
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+fn count_valid<'a>(msgs: &[&'a str], rules: &HashMap<usize, Vec<Rule<'a>>>) -> DynResult<usize> {
+    let mut count = 0;
+    for &msg in msgs {
+        if validate_msg(msg, rules, 0, 0)? == Some("") {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let (rules, msgs) = munge_input!(input);
 
-    Ok(msgs
-        .filter(|msg| validate_msg(msg, &rules, 0, 0) == Some(""))
-        .count())
+    count_valid(&msgs, &rules)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let (mut rules, msgs) = munge_input!(input);
 
-    *rules.get_mut(&8).unwrap() = vec![Rule::Seq(vec![42]), Rule::Seq(vec![42, 8])];
-    *rules.get_mut(&11).unwrap() = vec![Rule::Seq(vec![42, 31]), Rule::Seq(vec![42, 11, 31])];
+    rules.insert(8, vec![Rule::Seq(vec![42]), Rule::Seq(vec![42, 8])]);
+    rules.insert(11, vec![Rule::Seq(vec![42, 31]), Rule::Seq(vec![42, 11, 31])]);
 
-    Ok(msgs
-        .filter(|msg| validate_msg(msg, &rules, 0, 0) == Some(""))
-        .count())
+    count_valid(&msgs, &rules)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate rules with the same overall shape as the real input (i.e: `0: 8
+/// 11`, `8: 42`, and `11: 42 31`), followed by `size` messages.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    // rules 42 and 31 each match half of all `CHUNK` character long strings
+    const CHUNK: usize = 4;
+
+    /// Add rules for a trie which matches exactly the strings in `set`.
+    fn trie(
+        set: &[String],
+        id: usize,
+        (a, b): (usize, usize),
+        ids: &mut impl Iterator<Item = usize>,
+        rules: &mut Vec<String>,
+    ) {
+        let mut alts = Vec::new();
+        for &(c, c_id) in &[('a', a), ('b', b)] {
+            let tails = set
+                .iter()
+                .filter_map(|s| s.strip_prefix(c))
+                .map(String::from)
+                .collect::<Vec<_>>();
+            match tails.first() {
+                None => {}
+                Some(tail) if tail.is_empty() => alts.push(c_id.to_string()),
+                Some(_) => {
+                    let tail_id = ids.next().unwrap();
+                    trie(&tails, tail_id, (a, b), ids, rules);
+                    alts.push(format!("{} {}", c_id, tail_id));
+                }
+            }
+        }
+        rules.push(format!("{}: {}", id, alts.join(" | ")));
+    }
+
+    let mut chunks = (0..1 << CHUNK)
+        .map(|n| {
+            (0..CHUNK)
+                .map(|i| if n >> i & 1 == 1 { 'a' } else { 'b' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut chunks);
+    let (set42, set31) = chunks.split_at(chunks.len() / 2);
+
+    let mut ids = (1..200)
+        .filter(|id| ![8, 11, 31, 42].contains(id))
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut ids);
+    let mut ids = ids.into_iter();
+    let (a, b) = (ids.next().unwrap(), ids.next().unwrap());
+
+    let mut rules = vec![
+        "0: 8 11".to_string(),
+        "8: 42".to_string(),
+        "11: 42 31".to_string(),
+        format!("{}: \"a\"", a),
+        format!("{}: \"b\"", b),
+    ];
+    trie(set42, 42, (a, b), &mut ids, &mut rules);
+    trie(set31, 31, (a, b), &mut ids, &mut rules);
+    rng.shuffle(&mut rules);
+
+    let msgs = (0..size.max(1))
+        .map(|_| {
+            let (n42, n31) = match rng.range(0..3) {
+                // matches the original rules
+                0 => (2, 1),
+                // only matches the looping rules
+                1 => {
+                    let n42 = rng.range(2..6);
+                    (n42, rng.range(1..n42))
+                }
+                // doesn't match either
+                _ => {
+                    let n31 = rng.range(1..4);
+                    (rng.range(0..n31 + 1), n31)
+                }
+            };
+            let mut msg = String::new();
+            for _ in 0..n42 {
+                msg.push_str(rng.choose(set42));
+            }
+            for _ in 0..n31 {
+                msg.push_str(rng.choose(set31));
+            }
+            msg
+        })
+        .join("\n");
+
+    format!("{}\n\n{}", rules.join("\n"), msgs)
+}
 
-    const EXAMPLE_1: &str = r#"
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1, EXAMPLE_2];
+
+const EXAMPLE_1: &str = r#"
 0: 4 1 5
 1: 2 3 | 3 2
 2: 4 4 | 5 5
@@ -123,16 +237,7 @@ aaabbb
 aaaabbb
 "#;
 
-    #[test]
-    fn q1_e1() {
-        let input = EXAMPLE_1;
-        let expected = { 2 };
-        let q = q1;
-
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
-    }
-
-    const EXAMPLE_2: &str = r#"
+const EXAMPLE_2: &str = r#"
 42: 9 14 | 10 1
 9: 14 27 | 1 26
 10: 23 14 | 28 1
@@ -182,13 +287,26 @@ babaaabbbaaabaababbaabababaaab
 aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba
 "#;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn q1_e1() {
+        let input = EXAMPLE_1;
+        let expected = { 2 };
+        let q = q1;
+
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }
+
     #[test]
     fn q1_e2() {
         let input = EXAMPLE_2;
         let expected = { 3 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -197,6 +315,6 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba
         let expected = { 12 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
@@ -1,25 +1,35 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input
+        let input = input
             .split('\n')
-            .map(|ln| -> Option<_> {
-                let mut ln = ln.split(' ');
-                let mut range = ln.next()?.split('-');
-                let min = range.next()?.parse::<usize>().ok()?;
-                let max = range.next()?.parse::<usize>().ok()?;
-                let c = ln.next()?.chars().next()?;
-                let pass = ln.next()?;
-                Some(((min, max), c, pass.to_owned()))
+            .map(|ln| -> Result<_, InputError> {
+                let mut parts = ln.split(' ');
+                let range = parts.next().unwrap();
+                let (min, max) = range
+                    .split_once('-')
+                    .ok_or_at(input, range, "expected range (e.g: 1-3)")?;
+                let min = min.parse::<usize>().at(input, min)?;
+                let max = max.parse::<usize>().at(input, max)?;
+                let c = parts.next().ok_or_at(input, ln, "missing letter")?;
+                let c = c
+                    .strip_suffix(':')
+                    .and_then(|c| c.chars().next())
+                    .ok_or_at(input, c, "expected letter (e.g: a:)")?;
+                let pass = parts.next().ok_or_at(input, ln, "missing password")?;
+                Ok(((min, max), c, pass.to_owned()))
             })
-            .collect::<Option<Vec<_>>>()
-            .ok_or("invalid input")?
+            .collect::<Result<Vec<_>, _>>()?;
+        aoc::mark_parsed();
+        input
     }};
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
 
     let valid = input
@@ -32,40 +42,62 @@ pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
     Ok(valid)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
 
     let valid = input
         .into_iter()
         .filter(|((a, b), c, pass)| {
-            let (a, b) = (a - 1, b - 1);
-            let c = *c as u8;
-            let pass = pass.as_bytes();
-
-            (pass[a] == c || pass[b] == c) && pass[a] != pass[b]
+            // positions are 1-indexed, and positions outside the password
+            // (including 0) never contain the letter
+            let has_c = |pos: usize| pos.checked_sub(1).and_then(|i| pass.chars().nth(i)) == Some(*c);
+            has_c(*a) != has_c(*b)
         })
         .count();
 
     Ok(valid)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate `size` password policies and passwords.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let len = rng.range(3..20);
+            let min = rng.range(1..len);
+            let max = rng.range(min + 1..len + 1);
+            let c = (b'a' + rng.range(0..26) as u8) as char;
+            // bias passwords towards containing the policy's letter
+            let pass = (0..len)
+                .map(|_| match rng.chance(1, 3) {
+                    true => c,
+                    false => (b'a' + rng.range(0..26) as u8) as char,
+                })
+                .collect::<String>();
+            format!("{}-{} {}: {}", min, max, c, pass)
+        })
+        .join("\n")
+}
 
-    const EXAMPLE_1: &str = "
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = "
 1-3 a: abcde
 1-3 b: cdefg
 2-9 c: ccccccccc
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = 2;
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -74,6 +106,6 @@ mod tests {
         let expected = 1;
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
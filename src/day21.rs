@@ -1,23 +1,27 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input
+        let input = input
             .split('\n')
-            .map(|ln| -> DynResult<_> {
-                let mut sections = ln.split(" (contains ");
-                let ings = sections.next().unwrap().split(' ').collect::<HashSet<_>>();
-                let allergens = sections
-                    .next()
-                    .ok_or("missing allergens")?
+            .map(|ln| -> Result<_, InputError> {
+                let (ings, allergens) = ln
+                    .split_once(" (contains ")
+                    .ok_or_at(input, ln, "missing allergens")?;
+                let ings = ings.split(' ').collect::<HashSet<_>>();
+                let allergens = allergens
                     .strip_suffix(')')
-                    .ok_or("malformed allergens")?
+                    .ok_or_at(input, allergens, "malformed allergens")?
                     .split(", ")
                     .collect::<HashSet<_>>();
                 Ok((ings, allergens))
             })
-            .collect::<DynResult<Vec<_>>>()?
+            .collect::<Result<Vec<_>, _>>()?;
+        aoc::mark_parsed();
+        input
     }};
 }
 
@@ -39,7 +43,7 @@ fn get_allergen_guesses<'a>(
     guesses
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
 
     let guesses = get_allergen_guesses(&input);
@@ -75,7 +79,7 @@ pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
     Ok(ans)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<String> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<String> {
     let input = munge_input!(input);
 
     let mut guesses = get_allergen_guesses(&input);
@@ -115,24 +119,99 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<String> {
         .join(","))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate `size` foods (plus two for each allergen), where each allergen's
+/// ingredient can be deduced purely by elimination.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    const ALLERGENS: &[&str] = &[
+        "dairy",
+        "eggs",
+        "fish",
+        "nuts",
+        "peanuts",
+        "sesame",
+        "shellfish",
+        "soy",
+        "wheat",
+    ];
+
+    let mut allergens = ALLERGENS.to_vec();
+    rng.shuffle(&mut allergens);
+    allergens.truncate((size / 4).clamp(1, ALLERGENS.len()));
+    let n = allergens.len();
+
+    let mut ings = BTreeSet::new();
+    while ings.len() < n + 40 {
+        ings.insert(rng.word(4..8));
+    }
+    let mut ings = ings.into_iter().collect::<Vec<_>>();
+    rng.shuffle(&mut ings);
+    let ings = ings.iter().map(String::as_str).collect::<Vec<_>>();
+    // `bad[k]` contains `allergens[k]`
+    let (bad, safe) = ings.split_at(n);
+
+    // the k'th allergen is listed by a pair of foods which only have the first
+    // k + 1 bad ingredients in common, so once the first k allergens have been
+    // found, the k'th allergen's ingredient is obvious
+    let mut foods = Vec::new();
+    let (safe1, safe2) = safe.split_at(safe.len() / 2);
+    for k in 0..n {
+        for &safe in &[safe1, safe2] {
+            let mut food = bad[..=k].to_vec();
+            food.extend(safe.iter().filter(|_| rng.chance(1, 4)));
+            foods.push((food, vec![allergens[k]]));
+        }
+    }
 
-    const EXAMPLE_1: &str = r#"
+    for _ in 0..size {
+        let listed = allergens
+            .iter()
+            .copied()
+            .filter(|_| rng.chance(1, 3))
+            .collect::<Vec<_>>();
+        let listed = match listed.is_empty() {
+            true => vec![*rng.choose(&allergens)],
+            false => listed,
+        };
+        // foods don't necessarily list every allergen they contain
+        let mut food = (0..n)
+            .filter(|&k| listed.contains(&allergens[k]) || rng.chance(1, 4))
+            .map(|k| bad[k])
+            .collect::<Vec<_>>();
+        food.extend(safe.iter().filter(|_| rng.chance(1, 5)));
+        foods.push((food, listed));
+    }
+    rng.shuffle(&mut foods);
+
+    foods
+        .iter_mut()
+        .map(|(food, listed)| {
+            rng.shuffle(food);
+            format!("{} (contains {})", food.join(" "), listed.join(", "))
+        })
+        .join("\n")
+}
+
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = r#"
 mxmxvkd kfcds sqjhc nhms (contains dairy, fish)
 trh fvjkl sbzzf mxmxvkd (contains dairy)
 sqjhc fvjkl (contains soy)
 sqjhc mxmxvkd sbzzf (contains fish)
 "#;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 5 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -141,6 +220,6 @@ sqjhc mxmxvkd sbzzf (contains fish)
         let expected = { "mxmxvkd,sqjhc,fvjkl" };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
@@ -1,23 +1,32 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
         let mut players = input.split("\n\n");
-        let p1 = players
-            .next()
-            .unwrap()
-            .split('\n')
-            .skip(1)
-            .map(|ln| ln.parse::<usize>())
-            .collect::<Result<VecDeque<_>, _>>()?;
-        let p2 = players
-            .next()
-            .ok_or("missing player 2")?
-            .split('\n')
-            .skip(1)
-            .map(|ln| ln.parse::<usize>())
-            .collect::<Result<VecDeque<_>, _>>()?;
+        let parse_deck = |player: &str| -> Result<VecDeque<usize>, InputError> {
+            let deck = player
+                .split('\n')
+                .skip(1)
+                .map(|ln| match ln.parse::<usize>().at(input, ln)? {
+                    0 => Err(InputError::new(input, ln, "cards must be non-zero")),
+                    card => Ok(card),
+                })
+                .collect::<Result<VecDeque<_>, _>>()?;
+            if deck.is_empty() {
+                return Err(InputError::new(input, player, "deck is empty"));
+            }
+            Ok(deck)
+        };
+        let p1 = parse_deck(players.next().unwrap())?;
+        let p2 = parse_deck(
+            players
+                .next()
+                .ok_or_at(input, &input[input.len()..], "missing player 2")?,
+        )?;
+        aoc::mark_parsed();
         (p1, p2)
     }};
 }
@@ -27,7 +36,10 @@ enum Winner {
     Crab,
 }
 
-fn combat<const RECURSE: bool>(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize>) -> usize {
+fn combat<const RECURSE: bool>(
+    p1: &mut VecDeque<usize>,
+    p2: &mut VecDeque<usize>,
+) -> DynResult<usize> {
     fn combat_helper<const RECURSE: bool>(
         p1: &mut VecDeque<usize>,
         p2: &mut VecDeque<usize>,
@@ -87,25 +99,40 @@ fn combat<const RECURSE: bool>(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize
     .iter()
     .rev()
     .enumerate()
-    .map(|(i, x)| (i + 1) * x)
-    .sum::<usize>()
+    .try_fold(0usize, |score, (i, x)| score.checked_add((i + 1).checked_mul(*x)?))
+    .ok_or_else(|| "score overflowed".into())
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let (mut p1, mut p2) = munge_input!(input);
-    Ok(combat::<false>(&mut p1, &mut p2))
+    combat::<false>(&mut p1, &mut p2)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let (mut p1, mut p2) = munge_input!(input);
-    Ok(combat::<true>(&mut p1, &mut p2))
+    combat::<true>(&mut p1, &mut p2)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate two equally sized decks using `size` (rounded up to an even
+/// number, and at most 50) distinct cards. Recursive combat gets _really_ slow
+/// with bigger decks.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.clamp(2, 50);
+    let size = size + size % 2;
+    let mut cards = (1..=size).collect::<Vec<_>>();
+    rng.shuffle(&mut cards);
+    let (p1, p2) = cards.split_at(size / 2);
+    format!(
+        "Player 1:\n{}\n\nPlayer 2:\n{}",
+        p1.iter().join("\n"),
+        p2.iter().join("\n")
+    )
+}
 
-    const EXAMPLE_1: &str = r#"
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1, EXAMPLE_2];
+
+const EXAMPLE_1: &str = r#"
 Player 1:
 9
 2
@@ -121,13 +148,28 @@ Player 2:
 10
 "#;
 
+const EXAMPLE_2: &str = r#"
+Player 1:
+43
+19
+
+Player 2:
+2
+29
+14
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 306 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -136,24 +178,13 @@ Player 2:
         let expected = { 291 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
-    const EXAMPLE_2: &str = r#"
-Player 1:
-43
-19
-
-Player 2:
-2
-29
-14
-"#;
-
     #[test]
     fn q2_e2_terminates() {
         let input = EXAMPLE_2;
         let q = q2;
-        q(input.trim(), &[]).unwrap();
+        q(input.trim(), &Options::default()).unwrap();
     }
 }
@@ -1,25 +1,38 @@
 use crate::prelude::*;
 
+const MOVES: Opt = Opt {
+    name: "moves",
+    kind: OptKind::Usize,
+    default: "100",
+    help: "number of moves the crab makes",
+};
+
+pub const OPTIONS: &[Opt] = &[MOVES];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
+        if input.len() != 9 {
+            return Err(InputError::new(input, input, "expected 9 cups").into());
+        }
         let input = input
-            .chars()
-            .map(|c| ((c as u8) - b'0') as u32)
-            .collect::<VecDeque<_>>();
-        if input.len() != 9 || input.iter().any(|c| !(1..=9).contains(c)) {
-            return Err("invalid input".into());
+            .char_indices()
+            .map(|(i, c)| match c.to_digit(10) {
+                Some(n @ 1..=9) => Ok(n),
+                _ => Err(InputError::new(input, &input[i..], "expected a digit 1-9")),
+            })
+            .collect::<Result<VecDeque<_>, _>>()?;
+        if input.iter().collect::<HashSet<_>>().len() != 9 {
+            return Err(InputError::new($input, $input, "expected each cup to appear once").into());
         }
+        aoc::mark_parsed();
         input
     }};
 }
 
-pub fn q1(input: &str, args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
-    let iters = match args.get(0) {
-        Some(iters) => iters.parse::<usize>().map_err(|_| "invalid num iters")?,
-        None => 100,
-    };
+    let iters = opts.get::<usize>(&MOVES)?;
 
     let mut cups = input;
 
@@ -59,7 +72,7 @@ pub fn q1(input: &str, args: &[&str]) -> DynResult<usize> {
     Ok(ans)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<u64> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<u64> {
     let input = munge_input!(input);
 
     let _ = input;
@@ -67,21 +80,31 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<u64> {
     Ok(0)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate a random arrangement of the 9 cups (`size` is ignored).
+pub fn generate(rng: &mut Rng, _size: usize) -> String {
+    let mut cups = (1..=9).collect::<Vec<_>>();
+    rng.shuffle(&mut cups);
+    cups.iter().join("")
+}
+
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
 
-    const EXAMPLE_1: &str = r#"
+const EXAMPLE_1: &str = r#"
 389125467
 "#;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 92658374 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &["10"]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default().with("moves", "10")).unwrap(), expected);
     }
 
     #[test]
@@ -90,7 +113,7 @@ mod tests {
         let expected = { 67384529 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -99,6 +122,6 @@ mod tests {
         let expected = { 934001 * 159792 };
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
@@ -1,41 +1,62 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input.split('\n').map(|ln| ln.as_bytes())
+        let trees = Grid::parse(input, |c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err("expected '.' or '#'"),
+        })?;
+        aoc::mark_parsed();
+        trees
     }};
 }
 
-fn count_trees<'a>(lns: impl Iterator<Item = &'a [u8]>, (dx, dy): (usize, usize)) -> usize {
-    lns.step_by(dy)
+fn count_trees(trees: &Grid<bool>, (dx, dy): (usize, usize)) -> usize {
+    (0..trees.height())
+        .step_by(dy)
         .zip((0..).step_by(dx))
-        .filter(|(ln, x)| ln[x % ln.len()] == b'#')
+        // the pattern repeats to the right
+        .filter(|&(y, x)| *trees.get_wrapping(x, y as isize))
         .count()
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
-    let total = count_trees(input, (3, 1));
+    let total = count_trees(&input, (3, 1));
     Ok(total)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
     let total = slopes
         .into_iter()
-        .map(|slope| count_trees(input.clone(), slope))
-        .product();
+        .map(|slope| count_trees(&input, slope))
+        .try_fold(1usize, |total, trees| total.checked_mul(trees))
+        .ok_or("too many trees")?;
 
     Ok(total)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate a map `size` rows tall (and 31 columns wide, like the real input).
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            (0..31)
+                .map(|_| if rng.chance(1, 4) { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .join("\n")
+}
 
-    const EXAMPLE_1: &str = "
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = "
 ..##.......
 #...#...#..
 .#....#..#.
@@ -49,13 +70,17 @@ mod tests {
 .#..#...#.#
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = 7;
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -64,6 +89,6 @@ mod tests {
         let expected = 336;
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
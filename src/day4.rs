@@ -1,26 +1,24 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 const REQ_FIELDS: &[&str] = &["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"]; //, "cid"];
 const EYE_COLORS: &[&str] = &["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
 
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input
+        let input = input
             .split("\n\n")
             .map(|praw| {
                 praw.split('\n')
                     .flat_map(|ln| ln.split(' '))
-                    .map(|f| {
-                        let mut f = f.split(':');
-                        let k = f.next()?;
-                        let v = f.next()?;
-                        Some((k, v))
-                    })
-                    .collect::<Option<HashMap<_, _>>>()
+                    .map(|f| f.split_once(':').ok_or_at(input, f, "invalid k/v pair"))
+                    .collect::<Result<HashMap<_, _>, _>>()
             })
-            .collect::<Option<Vec<_>>>()
-            .ok_or("invalid k/v pair".to_string())?
+            .collect::<Result<Vec<_>, _>>()?;
+        aoc::mark_parsed();
+        input
     }};
 }
 
@@ -61,13 +59,13 @@ fn validate_values<'a>(p: &HashMap<&'a str, &'a str>) -> bool {
     validate_values_inner(p).unwrap_or(false)
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     let valid = input.into_iter().filter(validate_req_field).count();
     Ok(valid)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     let valid = input
         .into_iter()
@@ -77,11 +75,81 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
     Ok(valid)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate `size` passports, some of which are missing fields, or have
+/// invalid values.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let hex = |rng: &mut Rng, len: usize| {
+        (0..len)
+            .map(|_| *rng.choose(b"0123456789abcdef") as char)
+            .collect::<String>()
+    };
+
+    let digits = |rng: &mut Rng, len: usize| {
+        (0..len)
+            .map(|_| (b'0' + rng.range(0..10) as u8) as char)
+            .collect::<String>()
+    };
+
+    (0..size.max(1))
+        .map(|_| {
+            let mut fields = Vec::new();
+            for &field in REQ_FIELDS.iter().chain(&["cid"]) {
+                let present = match field {
+                    "cid" => rng.chance(1, 2),
+                    _ => rng.chance(19, 20),
+                };
+                if !present {
+                    continue;
+                }
+
+                let valid = rng.chance(9, 10);
+                let val = match field {
+                    "byr" if valid => rng.range(1920..2003).to_string(),
+                    "iyr" if valid => rng.range(2010..2021).to_string(),
+                    "eyr" if valid => rng.range(2020..2031).to_string(),
+                    "byr" | "iyr" | "eyr" => rng.range(1900..2050).to_string(),
+                    "hgt" if valid => match rng.chance(1, 2) {
+                        true => format!("{}cm", rng.range(150..194)),
+                        false => format!("{}in", rng.range(59..77)),
+                    },
+                    "hgt" => match rng.chance(1, 2) {
+                        true => rng.range(50..200).to_string(),
+                        false => format!("{}cm", rng.range(50..150)),
+                    },
+                    "hcl" if valid => format!("#{}", hex(rng, 6)),
+                    "hcl" => match rng.chance(1, 2) {
+                        true => hex(rng, 6),
+                        false => format!("#{}", hex(rng, 5)),
+                    },
+                    "ecl" if valid => rng.choose(EYE_COLORS).to_string(),
+                    "ecl" => rng.word(3..4),
+                    "pid" if valid => digits(rng, 9),
+                    "pid" => {
+                        let len = *rng.choose(&[8, 10]);
+                        digits(rng, len)
+                    }
+                    _ => rng.range(100..350).to_string(),
+                };
+                fields.push(format!("{}:{}", field, val));
+            }
+            rng.shuffle(&mut fields);
+
+            let mut passport = String::new();
+            for (i, field) in fields.iter().enumerate() {
+                if i != 0 {
+                    passport.push(if rng.chance(1, 3) { '\n' } else { ' ' });
+                }
+                passport.push_str(field);
+            }
+            passport
+        })
+        .join("\n\n")
+}
 
-    const EXAMPLE_1: &str = "
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1, EXAMPLE_INVALID, EXAMPLE_VALID];
+
+const EXAMPLE_1: &str = "
 ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
 byr:1937 iyr:2017 cid:147 hgt:183cm
 
@@ -97,16 +165,7 @@ hcl:#cfa07d eyr:2025 pid:166559648
 iyr:2011 ecl:brn hgt:59in
 ";
 
-    #[test]
-    fn q1_e1() {
-        let input = EXAMPLE_1;
-        let expected = 2;
-        let q = q1;
-
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
-    }
-
-    const EXAMPLE_INVALID: &str = "
+const EXAMPLE_INVALID: &str = "
 eyr:1972 cid:100
 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
 
@@ -122,7 +181,7 @@ eyr:2038 hcl:74454a iyr:2023
 pid:3556412378 byr:2007
 ";
 
-    const EXAMPLE_VALID: &str = "
+const EXAMPLE_VALID: &str = "
 pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
 hcl:#623a2f
 
@@ -137,13 +196,26 @@ eyr:2022
 iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn q1_e1() {
+        let input = EXAMPLE_1;
+        let expected = 2;
+        let q = q1;
+
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }
+
     #[test]
     fn q2_e1() {
         let input = EXAMPLE_INVALID;
         let expected = 0;
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -152,6 +224,6 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719
         let expected = 4;
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
@@ -1,24 +1,32 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input.split('\n').map(|ln| {
-            ln.chars()
-                .fold(0, |a, c| a << 1 | matches!(c, 'B' | 'R') as usize)
-        })
+        let input = input
+            .split('\n')
+            .map(|ln| {
+                ln.chars()
+                    .fold(0, |a, c| a << 1 | matches!(c, 'B' | 'R') as usize)
+            })
+            .collect::<Vec<_>>();
+        aoc::mark_parsed();
+        input
     }};
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
-    let max = input.max().ok_or("invalid input")?;
+    let max = input.into_iter().max().ok_or("invalid input")?;
     Ok(max)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
-    let ids = input.sorted().collect::<Vec<_>>();
+    let mut ids = input;
+    ids.sort_unstable();
     // since it's a packed flight, once the seat IDs are sorted, they are guaranteed
     // to be monotonically increasing by 1, _except_ for one index, which increases
     // by 2. That "hole" is our seat ID.
@@ -34,26 +42,82 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
     // NOTE: `partition_point_enumerated` is a custom slice extension method defined
     // in the `crate::prelude`.
     let hole = ids.partition_point_enumerated(|i, v| v - ids[0] == i);
-    let seat = ids[hole] - 1;
+    let seat = ids.get(hole).ok_or("no empty seats")? - 1;
     Ok(seat)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate `size` boarding passes (at most 1022) with contiguous seat IDs,
+/// except for a single missing seat somewhere in the middle.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.clamp(2, 1022);
+    let first = rng.range(0..1024 - size);
+    let missing = first + rng.range(1..size);
+
+    let mut ids = (first..=first + size)
+        .filter(|&id| id != missing)
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut ids);
 
-    const EXAMPLE_1: &str = "
+    ids.into_iter()
+        .map(|id| {
+            (0..10)
+                .rev()
+                .map(|bit| match (bit >= 3, id >> bit & 1 == 1) {
+                    (true, false) => 'F',
+                    (true, true) => 'B',
+                    (false, false) => 'L',
+                    (false, true) => 'R',
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = "
 BFFFBBFRRR
 FFFBBBFRRR
 BBFFBBFRLL
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::prop;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = 820;
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }
+
+    fn reference_ids(input: &str) -> Vec<usize> {
+        input
+            .split('\n')
+            .map(|ln| {
+                let bin = ln.replace(&['F', 'L'][..], "0").replace(&['B', 'R'][..], "1");
+                usize::from_str_radix(&bin, 2).unwrap()
+            })
+            .collect()
+    }
+
+    fn reference_q2(input: &str) -> usize {
+        let ids = reference_ids(input);
+        let (min, max) = (*ids.iter().min().unwrap(), *ids.iter().max().unwrap());
+        (min..max).find(|id| !ids.contains(id)).unwrap()
+    }
+
+    #[test]
+    fn prop_matches_reference() {
+        let opts = Options::default();
+        let reference_q1 = |i: &str| reference_ids(i).into_iter().max().unwrap();
+        prop::check(1000, generate, |i| q1(i, &opts), reference_q1);
+        prop::check(1000, generate, |i| q2(i, &opts), reference_q2);
     }
 }
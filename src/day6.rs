@@ -1,38 +1,45 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input
+        let input = input
             .split("\n\n")
-            .map(|g| g.split('\n').map(|p| p.chars()))
+            .map(|g| g.split('\n').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        aoc::mark_parsed();
+        input
     }};
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
     let any = input
-        .map(|g| g.flatten().collect::<HashSet<_>>().len())
+        .iter()
+        .map(|g| g.iter().flat_map(|p| p.chars()).collect::<HashSet<_>>().len())
         .sum();
     Ok(any)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
 
     // this could be made marginally more efficient by lifting the fold into `g`
     // itself + counting the group size while calculating the char occurrence
     // hashmap, but I'm lazy, and this is fine.
     let all = input
+        .iter()
         .map(|g| {
-            let g_size = g.clone().count();
-            g.flatten()
+            g.iter()
+                .flat_map(|p| p.chars())
                 .fold(HashMap::new(), |mut m, c| {
                     *m.entry(c).or_insert(0) += 1;
                     m
                 })
                 .iter()
-                .filter(|(_, &v)| v == g_size)
+                .filter(|(_, &v)| v == g.len())
                 .count()
         })
         .sum();
@@ -40,11 +47,35 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
     Ok(all)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate `size` groups of answers.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            // questions everyone in the group answered "yes" to
+            let common = (b'a'..=b'z')
+                .filter(|_| rng.chance(1, 8))
+                .collect::<Vec<_>>();
+            (0..rng.range(1..6))
+                .map(|_| {
+                    let mut answers = (b'a'..=b'z')
+                        .filter(|c| common.contains(c) || rng.chance(1, 5))
+                        .map(|c| c as char)
+                        .collect::<Vec<_>>();
+                    if answers.is_empty() {
+                        answers.push((b'a' + rng.range(0..26) as u8) as char);
+                    }
+                    rng.shuffle(&mut answers);
+                    answers.into_iter().collect::<String>()
+                })
+                .join("\n")
+        })
+        .join("\n\n")
+}
 
-    const EXAMPLE_1: &str = "
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = "
 abc
 
 a
@@ -62,13 +93,17 @@ a
 b
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = 11;
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -77,6 +112,6 @@ b
         let expected = 6;
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
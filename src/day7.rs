@@ -1,48 +1,64 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input.split('\n').map(|ln| -> Option<_> {
-            let mut ln = ln.split(" bags contain ");
-
-            let mut parent_bag = ln.next()?.split(' ');
-            let parent_bag = (parent_bag.next()?, parent_bag.next()?);
-
-            let contents = (ln.next()?)
-                .split(", ")
-                .map(|e| -> Option<_> {
-                    let mut e = e.split(' ');
-                    let n = match e.next()? {
-                        "no" => return Some((0, ("", ""))),
-                        n => n.parse::<usize>().ok()?,
-                    };
-                    Some((n, (e.next()?, e.next()?)))
-                })
-                .filter(|e| e.map(|(n, _)| n == 0) != Some(true))
-                .collect::<Option<Vec<_>>>()?;
-
-            Some((parent_bag, contents))
-        })
+        let input = input
+            .split('\n')
+            .map(|ln| -> Result<_, InputError> {
+                let (parent_bag, contents) = ln
+                    .split_once(" bags contain ")
+                    .ok_or_at(input, ln, "expected \"<bag> bags contain <contents>\"")?;
+
+                let parent_bag = parent_bag
+                    .split_once(' ')
+                    .ok_or_at(input, parent_bag, "expected bag (e.g: shiny gold)")?;
+
+                let contents = contents
+                    .split(", ")
+                    .map(|e| -> Result<_, InputError> {
+                        let mut parts = e.split(' ');
+                        let n = match parts.next().unwrap() {
+                            "no" => return Ok((0, ("", ""))),
+                            n => n.parse::<usize>().at(input, n)?,
+                        };
+                        let bag = (parts.next(), parts.next());
+                        match bag {
+                            (Some(adj), Some(color)) => Ok((n, (adj, color))),
+                            _ => Err(InputError::new(input, e, "expected bag (e.g: 2 shiny gold bags)")),
+                        }
+                    })
+                    .filter(|e| !matches!(e, Ok((0, _))))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok((parent_bag, contents))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        aoc::mark_parsed();
+        input
     }};
 }
 
 type Bag<'a> = (&'a str, &'a str);
 type Bags<'a> = HashMap<Bag<'a>, Vec<(usize, Bag<'a>)>>;
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
 
     // HashMap<Bag, HashSet<Parent Bag>>, discarding the number
     let mut g: HashMap<_, HashSet<_>> = HashMap::new();
-    for p in input {
-        let (p, c) = p.ok_or("invalid input")?;
+    for (p, c) in input {
         for (_, b) in c {
             g.entry(b).or_default().insert(p);
         }
     }
 
-    let initial = g.get(&("shiny", "gold")).unwrap();
+    let initial = match g.get(&("shiny", "gold")) {
+        Some(initial) => initial,
+        None => return Ok(0),
+    };
 
     let mut v = initial.iter().collect::<HashSet<_>>();
     let mut q = initial.iter().collect::<Vec<_>>();
@@ -64,26 +80,121 @@ pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
     Ok(total)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
-    let bags = input.collect::<Option<Bags>>().ok_or("invalid input")?;
-
-    fn num_bags(bags: &Bags, parent_bag: &Bag) -> usize {
-        bags.get(parent_bag)
-            .unwrap()
-            .iter()
-            .map(|(n, bag)| n * (1 + num_bags(bags, bag)))
-            .sum::<usize>()
-    };
+    let bags = input.into_iter().collect::<Bags>();
+
+    // memoized, since bags can be reached by many different paths, and `path`
+    // is used to detect bags which (eventually) contain themselves
+    fn num_bags<'a>(
+        bags: &Bags<'a>,
+        bag: Bag<'a>,
+        path: &mut Vec<Bag<'a>>,
+        memo: &mut HashMap<Bag<'a>, usize>,
+    ) -> DynResult<usize> {
+        if let Some(&n) = memo.get(&bag) {
+            return Ok(n);
+        }
+        if path.contains(&bag) {
+            return Err(format!("{} {} bags contain themselves", bag.0, bag.1).into());
+        }
+        let contents = bags
+            .get(&bag)
+            .ok_or_else(|| format!("missing rule for {} {} bags", bag.0, bag.1))?;
+
+        path.push(bag);
+        let mut total = 0usize;
+        for &(n, inner) in contents {
+            let inner = num_bags(bags, inner, path, memo)?;
+            total = (inner.checked_add(1))
+                .and_then(|inner| inner.checked_mul(n))
+                .and_then(|inner| inner.checked_add(total))
+                .ok_or("too many bags")?;
+        }
+        path.pop();
+
+        memo.insert(bag, total);
+        Ok(total)
+    }
 
-    Ok(num_bags(&bags, &("shiny", "gold")))
+    num_bags(&bags, ("shiny", "gold"), &mut Vec::new(), &mut HashMap::new())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate rules for `size` differently colored bags (at most a few hundred),
+/// where "shiny gold" bags both contain, and are contained by, other bags.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    const ADJECTIVES: &[&str] = &[
+        "light", "dark", "bright", "muted", "shiny", "faded", "dotted", "vibrant", "clear", "dim",
+        "drab", "dull", "mirrored", "pale", "plaid", "posh", "striped", "wavy",
+    ];
+    const COLORS: &[&str] = &[
+        "red", "orange", "white", "yellow", "gold", "olive", "plum", "blue", "black", "aqua",
+        "beige", "bronze", "brown", "coral", "crimson", "cyan", "fuchsia", "gray", "green",
+        "indigo", "lavender", "lime", "magenta", "maroon", "purple", "salmon", "silver", "tan",
+        "teal", "tomato", "turquoise", "violet",
+    ];
+
+    let mut bags = ADJECTIVES
+        .iter()
+        .cartesian_product(COLORS)
+        .filter(|&(&adj, &color)| (adj, color) != ("shiny", "gold"))
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut bags);
+    let size = size.clamp(3, bags.len() + 1);
+    bags.truncate(size - 1);
+    // keep shiny gold bags towards the start, so plenty of bags contain them
+    let gold = rng.range(1..(size / 2).max(2));
+    bags.insert(gold, (&"shiny", &"gold"));
+
+    // bags only contain bags which come before them, so there are no cycles.
+    // `totals` tracks how many bags each bag contains, which is used to keep
+    // the answer to part 2 from blowing up.
+    let mut totals: Vec<usize> = Vec::new();
+    let mut rules = Vec::new();
+    for (i, (adj, color)) in bags.iter().enumerate() {
+        let mut contents: Vec<(usize, usize)> = Vec::new();
+        let mut total = 0;
+        let mut add = |contents: &mut Vec<_>, n: usize, j: usize| {
+            total += n * (1 + totals[j]);
+            contents.push((n, j));
+        };
+
+        if i == gold || i == gold + 1 {
+            let j = if i == gold { rng.range(0..i) } else { gold };
+            add(&mut contents, rng.range(1..4), j);
+        }
+        if i != 0 {
+            for _ in 0..rng.range(0..5) {
+                let (n, j) = (rng.range(1..6), rng.range(0..i));
+                if contents.iter().all(|&(_, k)| k != j) && n * (1 + totals[j]) < 10_000 {
+                    add(&mut contents, n, j);
+                }
+            }
+        }
+        totals.push(total);
+
+        let contents = match contents.is_empty() {
+            true => "no other bags".to_string(),
+            false => contents
+                .iter()
+                .map(|&(n, j)| {
+                    let (adj, color) = bags[j];
+                    let plural = if n == 1 { "" } else { "s" };
+                    format!("{} {} {} bag{}", n, adj, color, plural)
+                })
+                .join(", "),
+        };
+        rules.push(format!("{} {} bags contain {}.", adj, color, contents));
+    }
 
-    const EXAMPLE_1: &str = "
+    rng.shuffle(&mut rules);
+    rules.join("\n")
+}
+
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1, EXAMPLE_2];
+
+const EXAMPLE_1: &str = "
 light red bags contain 1 bright white bag, 2 muted yellow bags.
 dark orange bags contain 3 bright white bags, 4 muted yellow bags.
 bright white bags contain 1 shiny gold bag.
@@ -95,7 +206,7 @@ faded blue bags contain no other bags.
 dotted black bags contain no other bags.
 ";
 
-    const EXAMPLE_2: &str = "
+const EXAMPLE_2: &str = "
 shiny gold bags contain 2 dark red bags.
 dark red bags contain 2 dark orange bags.
 dark orange bags contain 2 dark yellow bags.
@@ -105,13 +216,17 @@ dark blue bags contain 2 dark violet bags.
 dark violet bags contain no other bags.
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = 4;
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -120,7 +235,7 @@ dark violet bags contain no other bags.
         let expected = 32;
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -129,6 +244,6 @@ dark violet bags contain no other bags.
         let expected = 126;
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 }
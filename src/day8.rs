@@ -1,66 +1,141 @@
 use crate::prelude::*;
 
+use crate::cfg;
+use crate::isa::{self, Flow, Isa, OpDef, MAX_ARGS, NUM_REGS};
+use crate::trace::{Trace, TraceEntry};
+
+pub const OPTIONS: &[Opt] = &[];
+
 #[derive(Debug)]
 pub enum InstrParseError {
     MissingOpcode,
     MissingData,
     InvalidData(core::num::ParseIntError),
     InvalidOpcode(String),
+    InvalidRegister(String),
+    UnexpectedData(String),
 }
 
 impl core::fmt::Display for InstrParseError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{:?}", self)
+        use InstrParseError::*;
+        match self {
+            MissingOpcode => write!(f, "missing opcode"),
+            MissingData => write!(f, "missing data"),
+            InvalidData(e) => write!(f, "invalid data: {}", e),
+            InvalidOpcode(op) => write!(f, "invalid opcode: {}", op),
+            InvalidRegister(r) => write!(f, "invalid register: {}", r),
+            UnexpectedData(data) => write!(f, "unexpected data: {}", data),
+        }
     }
 }
 
 impl std::error::Error for InstrParseError {}
 
-#[derive(Debug, Copy, Clone)]
+/// An instruction. The puzzle's own instructions get their own variants, and
+/// anything else is defined by an [`isa`] extension.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Instr {
     Nop(i64),
     Acc(i64),
     Jmp(i64),
+    Ext(&'static OpDef, [i64; MAX_ARGS]),
 }
 
 impl Instr {
+    /// Parse one of the puzzle's instructions (i.e: in the format it's
+    /// displayed in). See [`Isa::parse`] for parsing extensions too.
     pub fn parse(asm: &str) -> Result<Instr, InstrParseError> {
-        use InstrParseError::*;
+        Isa::handheld().parse(asm)
+    }
 
-        let mut asm = asm.split(' ');
-        let op = asm.next().ok_or(MissingOpcode)?;
-        let data = asm.next().ok_or(MissingData)?;
+    /// The instruction's opcode, and its operands.
+    pub fn parts(self) -> (&'static OpDef, [i64; MAX_ARGS]) {
+        match self {
+            Instr::Nop(n) => (&isa::NOP, [n, 0]),
+            Instr::Acc(n) => (&isa::ACC, [n, 0]),
+            Instr::Jmp(n) => (&isa::JMP, [n, 0]),
+            Instr::Ext(op, args) => (op, args),
+        }
+    }
 
-        let instr = match op {
-            "nop" => Instr::Nop(data.parse().map_err(InvalidData)?),
-            "acc" => Instr::Acc(data.parse().map_err(InvalidData)?),
-            "jmp" => Instr::Jmp(data.parse().map_err(InvalidData)?),
-            _ => return Err(InvalidOpcode(op.into())),
-        };
+    /// Swap a `jmp` for a `nop` (or vice versa). Returns `None` for anything
+    /// else.
+    pub fn flipped(self) -> Option<Instr> {
+        match self {
+            Instr::Nop(offset) => Some(Instr::Jmp(offset)),
+            Instr::Jmp(offset) => Some(Instr::Nop(offset)),
+            Instr::Acc(_) | Instr::Ext(..) => None,
+        }
+    }
+}
 
-        Ok(instr)
+/// Formatted the same way as the puzzle input, so it round-trips through
+/// [`Instr::parse`] (or [`Isa::parse`], for extensions).
+impl core::fmt::Display for Instr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (op, args) = self.parts();
+        op.fmt(f, &args)
     }
 }
 
+/// The handheld game console's VM.
+///
+/// ```
+/// use aoc20::day8::Vm;
+///
+/// let mut vm = Vm::new("acc +3\njmp +2\nacc +100\nacc -1").unwrap();
+/// assert!(vm.run_with_loop_detect().unwrap());
+/// assert_eq!(vm.acc, 2);
+/// ```
 pub struct Vm {
     pub program: Vec<Instr>,
 
     pub pc: usize,
     pub acc: i64,
+    /// General purpose registers, used by [`isa`] extensions.
+    pub regs: [i64; NUM_REGS],
+    /// I/O port queues, used by [`isa`] extensions. Ports aren't cleared on
+    /// reset, so input can be queued up beforehand.
+    pub ports: HashMap<i64, VecDeque<i64>>,
+
+    /// Number of instructions executed since the last reset.
+    pub steps: usize,
+    /// If set, every executed instruction is recorded here.
+    pub trace: Option<Trace>,
 }
 
 impl Vm {
-    pub fn new(asm: &str) -> Result<Vm, InstrParseError> {
+    pub fn new(asm: &str) -> Result<Vm, InputError> {
+        Vm::with_isa(asm, &Isa::handheld())
+    }
+
+    /// Parse a program which might use instructions from outside the puzzle.
+    pub fn with_isa(asm: &str, isa: &Isa) -> Result<Vm, InputError> {
         let program = asm
             .split('\n')
-            .map(Instr::parse)
+            .map(|ln| isa.parse(ln).at(asm, ln))
             .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(Vm {
+        Ok(Vm::from_program(program))
+    }
+
+    pub fn from_program(program: Vec<Instr>) -> Vm {
+        Vm {
             program,
             pc: 0,
             acc: 0,
-        })
+            regs: [0; NUM_REGS],
+            ports: HashMap::new(),
+            steps: 0,
+            trace: None,
+        }
+    }
+
+    /// Record every instruction executed from now on.
+    pub fn with_trace(mut self, trace: Trace) -> Vm {
+        self.trace = Some(trace);
+        self
     }
 
     pub fn cycle(&mut self) -> DynResult<()> {
@@ -68,22 +143,44 @@ impl Vm {
             return Err(format!("PC is out of bounds: {}", self.pc).into());
         }
 
-        match self.program[self.pc] {
-            Instr::Nop(_) => {}
-            Instr::Acc(v) => self.acc += v,
-            Instr::Jmp(offset) => self.pc = self.pc.wrapping_add((offset - 1) as usize),
+        let (pc, instr, acc_before) = (self.pc, self.program[self.pc], self.acc);
+        let (op, args) = instr.parts();
+        match (op.exec)(self, args)? {
+            Flow::Next => self.pc = self.pc.wrapping_add(1),
+            Flow::Jump(offset) => self.pc = self.pc.wrapping_add(offset as usize),
+            Flow::Halt => self.pc = self.program.len(),
         }
 
-        self.pc = self.pc.wrapping_add(1);
+        if let Some(trace) = &mut self.trace {
+            trace.record(TraceEntry {
+                step: self.steps,
+                pc,
+                instr,
+                acc_before,
+                acc_after: self.acc,
+            })?;
+        }
+        self.steps += 1;
         Ok(())
     }
 
+    /// Restart the program. In-memory traces are cleared too, but anything
+    /// already written out stays there.
     pub fn reset(&mut self) {
         self.pc = 0;
         self.acc = 0;
+        self.regs = [0; NUM_REGS];
+        self.steps = 0;
+        if let Some(trace) = &mut self.trace {
+            trace.clear();
+        }
     }
 
     /// Returns true if the program terminates, or false if a loop was detected.
+    ///
+    /// Only the pc is checked, which assumes (as with the puzzle's own
+    /// instructions) that the pc alone determines what happens next. Use
+    /// [`Vm::run`] for programs using conditional jumps.
     pub fn run_with_loop_detect(&mut self) -> DynResult<bool> {
         let mut v = std::collections::HashSet::new();
         loop {
@@ -99,44 +196,126 @@ impl Vm {
             self.cycle()?;
         }
     }
+
+    /// Run until the program terminates, or `limit` instructions have been
+    /// executed. Returns true if the program terminated.
+    pub fn run(&mut self, limit: usize) -> DynResult<bool> {
+        for _ in 0..limit {
+            if self.pc == self.program.len() {
+                return Ok(true);
+            }
+            self.cycle()?;
+        }
+        Ok(self.pc == self.program.len())
+    }
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<i64> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<i64> {
     let mut vm = Vm::new(input)?;
+    aoc::mark_parsed();
 
-    let terminates = vm.run_with_loop_detect()?;
-    assert!(!terminates, "input unexpectedly terminated");
+    if vm.run_with_loop_detect()? {
+        return Err("input unexpectedly terminated".into());
+    }
 
     Ok(vm.acc)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<i64> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<i64> {
     let mut vm = Vm::new(input)?;
+    aoc::mark_parsed();
 
-    for i in 0..vm.program.len() {
-        let bak = vm.program[i];
-        vm.program[i] = match vm.program[i] {
-            Instr::Acc(_) => continue,
-            Instr::Nop(offset) => Instr::Jmp(offset),
-            Instr::Jmp(offset) => Instr::Nop(offset),
+    let fix = cfg::find_fix(&vm.program).ok_or("could not find a valid mutation")?;
+    vm.program[fix] = vm.program[fix].flipped().unwrap();
+
+    if !vm.run_with_loop_detect()? {
+        return Err("fixed program still loops".into());
+    }
+    Ok(vm.acc)
+}
+
+/// Generate a program with roughly `size` instructions, which loops forever,
+/// but which can be fixed by flipping exactly one `jmp` / `nop`.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Slot {
+        /// An instruction which is executed by the fixed program.
+        Path,
+        /// A `jmp` (on the path) over the next `n` dead instructions.
+        Skip(usize),
+        /// An instruction which is never executed by the fixed program.
+        Dead,
+    }
+
+    let size = size.max(8);
+    loop {
+        let mut slots = Vec::new();
+        while slots.len() < size - 1 {
+            if rng.chance(1, 3) && slots.len() + 6 < size {
+                let n = rng.range(2..5);
+                slots.push(Slot::Skip(n));
+                slots.resize(slots.len() + n, Slot::Dead);
+            } else {
+                slots.push(Slot::Path);
+            }
+        }
+        // the fixed program terminates by running off the end
+        slots.push(Slot::Path);
+
+        let path = (0..slots.len())
+            .filter(|&i| slots[i] != Slot::Dead)
+            .collect::<Vec<_>>();
+        let bug = *rng.choose(&path[..path.len() - 1]);
+
+        // dead code (and the bug) jumps back to somewhere before the bug, so
+        // the only way to get past the bug is by fixing it. `path` is sorted,
+        // so the possible targets are always a prefix of it.
+        let jmp_back = |rng: &mut Rng, i: usize| {
+            let targets = &path[..path.partition_point(|&p| p <= i.min(bug))];
+            Instr::Jmp(*rng.choose(targets) as i64 - i as i64)
         };
 
-        if vm.run_with_loop_detect()? {
-            return Ok(vm.acc);
+        let mut program = Vec::with_capacity(slots.len());
+        let mut dead_left = 0;
+        for (i, slot) in slots.iter().enumerate() {
+            let instr = match slot {
+                Slot::Skip(n) => {
+                    dead_left = *n;
+                    Instr::Jmp(*n as i64 + 1)
+                }
+                Slot::Dead if dead_left <= 2 => {
+                    dead_left -= 1;
+                    jmp_back(rng, i)
+                }
+                _ => {
+                    dead_left = dead_left.saturating_sub(1);
+                    match rng.chance(1, 2) {
+                        true => Instr::Acc(rng.range_i64(-50..51)),
+                        // flipped nops always jump backwards
+                        false => Instr::Nop(-(rng.range(0..i + 1) as i64)),
+                    }
+                }
+            };
+            program.push(instr);
         }
 
-        vm.reset();
-        vm.program[i] = bak;
-    }
+        program[bug] = match program[bug] {
+            Instr::Jmp(offset) => Instr::Nop(offset),
+            _ => jmp_back(rng, bug),
+        };
 
-    Err("could not find a valid mutation".into())
+        // the construction above _should_ always work, but it's cheap enough to
+        // double check (`generated_has_one_fix` also checks it by brute force).
+        if cfg::find_fixes(&program).len() == 1 {
+            return program.iter().join("\n");
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
 
-    const EXAMPLE_1: &str = "
+const EXAMPLE_1: &str = "
 nop +0
 acc +1
 jmp +4
@@ -148,13 +327,19 @@ jmp -4
 acc +6
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::prop;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = 5;
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     #[test]
@@ -163,6 +348,64 @@ acc +6
         let expected = 8;
         let q = q2;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }
+
+    /// Flip each instruction in turn, re-running the whole program each time.
+    fn reference_q2(input: &str) -> i64 {
+        let mut vm = Vm::new(input).unwrap();
+        for i in 0..vm.program.len() {
+            let bak = vm.program[i];
+            vm.program[i] = match bak.flipped() {
+                Some(instr) => instr,
+                None => continue,
+            };
+
+            vm.reset();
+            if vm.run_with_loop_detect().unwrap() {
+                return vm.acc;
+            }
+            vm.program[i] = bak;
+        }
+        panic!("could not find a valid mutation")
+    }
+
+    #[test]
+    fn prop_matches_reference() {
+        let opts = Options::default();
+        prop::check(300, generate, |i| q2(i, &opts), reference_q2);
+    }
+
+    /// Count how many single instruction flips make the program terminate, or
+    /// `None` if it already terminates.
+    fn count_fixes(vm: &mut Vm) -> Option<usize> {
+        if vm.run_with_loop_detect().unwrap_or(false) {
+            return None;
+        }
+
+        let mut fixes = 0;
+        for i in 0..vm.program.len() {
+            let bak = vm.program[i];
+            vm.program[i] = match bak.flipped() {
+                Some(instr) => instr,
+                None => continue,
+            };
+            vm.reset();
+            if vm.run_with_loop_detect().unwrap_or(false) {
+                fixes += 1;
+            }
+            vm.program[i] = bak;
+        }
+        vm.reset();
+        Some(fixes)
+    }
+
+    #[test]
+    fn generated_has_one_fix() {
+        for seed in 0..20 {
+            let input = generate(&mut Rng::new(seed), 200);
+            let mut vm = Vm::new(&input).unwrap();
+            assert_eq!(count_fixes(&mut vm), Some(1), "seed {}", seed);
+        }
     }
 }
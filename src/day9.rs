@@ -1,20 +1,31 @@
 use crate::prelude::*;
 
+const PREAMBLE: Opt = Opt {
+    name: "preamble",
+    kind: OptKind::Usize,
+    default: "25",
+    help: "number of preceding numbers each number must be a sum of",
+};
+
+pub const OPTIONS: &[Opt] = &[PREAMBLE];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input
+        let input = input
             .split('\n')
-            .map(|s| s.parse::<usize>())
-            .collect::<Result<Vec<usize>, _>>()?
+            .map(|s| s.parse::<usize>().at(input, s))
+            .collect::<Result<Vec<usize>, _>>()?;
+        aoc::mark_parsed();
+        input
     }};
 }
 
 pub fn first_invalid(nums: &[usize], preamble: usize) -> DynResult<usize> {
     'outer: for g in nums.windows(preamble + 1) {
         let n = *g.last().ok_or("nums cannot be empty")?;
-        for combo in g.combinations::<2>() {
-            if combo.into_iter().sum::<usize>() == n {
+        for [a, b] in g.combinations::<2>() {
+            if a.checked_add(*b) == Some(n) {
                 continue 'outer;
             }
         }
@@ -24,23 +35,17 @@ pub fn first_invalid(nums: &[usize], preamble: usize) -> DynResult<usize> {
     Err("all numbers are valid".into())
 }
 
-pub fn q1(input: &str, args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
-    let preamble = match args.get(0) {
-        Some(n) => n.parse::<usize>().map_err(|_e| "invalid preamble len")?,
-        None => 25,
-    };
+    let preamble = opts.get::<usize>(&PREAMBLE)?;
 
     let first_invalid = first_invalid(&input, preamble)?;
     Ok(first_invalid)
 }
 
-pub fn q2(input: &str, args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
-    let preamble = match args.get(0) {
-        Some(n) => n.parse::<usize>().map_err(|_e| "invalid preamble len")?,
-        None => 25,
-    };
+    let preamble = opts.get::<usize>(&PREAMBLE)?;
 
     let target_sum = first_invalid(&input, preamble)?;
 
@@ -52,44 +57,74 @@ pub fn q2(input: &str, args: &[&str]) -> DynResult<usize> {
     let input = &input[0..max_i];
 
     // grows the sum by extending from the right-hand-side of the window, then
-    // shrinks the sum by shrinking from the left-hand-side of the window.
-    let mut sum = input[0] + input[1];
-    let (mut l, mut r) = (0, 2);
-    loop {
-        while sum < target_sum {
-            sum += match input.get(r) {
-                None => return Err("could not find valid span".into()),
-                Some(v) => {
-                    r += 1;
-                    v
-                }
-            };
+    // shrinks the sum by shrinking from the left-hand-side of the window. Sums
+    // are u128s, since the window can briefly exceed the target by up to a
+    // whole usize.
+    let target = target_sum as u128;
+    let (mut l, mut sum) = (0, 0u128);
+    for r in 0..input.len() {
+        sum += input[r] as u128;
+
+        // if a single number is bigger than the target, this empties the
+        // window, and it simply starts growing again from the next number
+        while sum > target {
+            sum -= input[l] as u128;
+            l += 1;
         }
 
-        if sum == target_sum {
+        if sum == target && r > l {
             // IMPROVEMENT: keep track of min and max while iterating
             // in the worst-case, this final iteration results in a full traversal of the
             // input array.
-            let (min, max) = input[l..r].iter().minmax().into_option().unwrap();
-            return Ok(min + max);
+            let (min, max) = input[l..=r].iter().minmax().into_option().unwrap();
+            return min.checked_add(*max).ok_or_else(|| "answer overflowed".into());
         }
+    }
 
-        while sum > target_sum {
-            sum -= input[l];
-            l += 1;
+    Err("could not find valid span".into())
+}
 
-            if l == r {
-                return Err("could not find valid span".into());
-            }
+/// Generate `size` numbers (at most 1000, to avoid overflow) using the default
+/// preamble length, where the last number is the only invalid one.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    const PREAMBLE: usize = 25;
+    let size = size.clamp(PREAMBLE + 5, 1000);
+
+    loop {
+        let mut nums = (1..=50).collect::<Vec<usize>>();
+        rng.shuffle(&mut nums);
+        nums.truncate(PREAMBLE);
+
+        while nums.len() < size - 1 {
+            // summing some of the smallest numbers in the window keeps the
+            // numbers from growing too quickly
+            let mut window = nums[nums.len() - PREAMBLE..].to_vec();
+            window.sort_unstable();
+            window.dedup();
+            let k = window.len().min(4);
+            let a = rng.range(0..k - 1);
+            let b = rng.range(a + 1..k);
+            nums.push(window[a] + window[b]);
+        }
+
+        // the invalid number is the sum of a contiguous set of numbers
+        let start = rng.range(0..size / 2);
+        let target = nums[start..start + rng.range(3..6)].iter().sum::<usize>();
+        let is_sum = nums[nums.len() - PREAMBLE..]
+            .iter()
+            .tuple_combinations()
+            .any(|(a, b)| a + b == target);
+        if !is_sum && !nums.contains(&target) {
+            nums.push(target);
+            return nums.iter().join("\n");
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
 
-    const EXAMPLE_1: &str = "
+const EXAMPLE_1: &str = "
 35
 20
 15
@@ -112,13 +147,19 @@ mod tests {
 576
 ";
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::prop;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = 127;
         let q = q1;
 
-        assert_eq!(q(input.trim(), &["5"]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default().with("preamble", "5")).unwrap(), expected);
     }
 
     #[test]
@@ -127,6 +168,44 @@ mod tests {
         let expected = 62;
         let q = q2;
 
-        assert_eq!(q(input.trim(), &["5"]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default().with("preamble", "5")).unwrap(), expected);
+    }
+
+    fn reference_q1(nums: &[usize]) -> usize {
+        for i in 25..nums.len() {
+            let window = &nums[i - 25..i];
+            let valid = (0..25).any(|j| (j + 1..25).any(|k| window[j] + window[k] == nums[i]));
+            if !valid {
+                return nums[i];
+            }
+        }
+        panic!("no invalid number")
+    }
+
+    fn reference_q2(nums: &[usize]) -> usize {
+        let target = reference_q1(nums);
+        let end = nums.iter().position(|&n| n == target).unwrap();
+        for i in 0..end {
+            for j in i + 2..=end {
+                let span = &nums[i..j];
+                if span.iter().sum::<usize>() == target {
+                    return span.iter().min().unwrap() + span.iter().max().unwrap();
+                }
+            }
+        }
+        panic!("no contiguous set")
+    }
+
+    #[test]
+    fn prop_matches_reference() {
+        let opts = Options::default();
+        let parse = |input: &str| {
+            input
+                .split('\n')
+                .map(|n| n.parse().unwrap())
+                .collect::<Vec<_>>()
+        };
+        prop::check(200, generate, |i| q1(i, &opts), |i| reference_q1(&parse(i)));
+        prop::check(200, generate, |i| q2(i, &opts), |i| reference_q2(&parse(i)));
     }
 }
@@ -1,13 +1,17 @@
 use crate::prelude::*;
 
+pub const OPTIONS: &[Opt] = &[];
+
 macro_rules! munge_input {
     ($input:ident) => {{
         let input = $input;
-        input.split('\n')
+        let input = input.split('\n').collect::<Vec<_>>();
+        aoc::mark_parsed();
+        input
     }};
 }
 
-pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q1(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
 
     let _ = input;
@@ -15,7 +19,7 @@ pub fn q1(input: &str, _args: &[&str]) -> DynResult<usize> {
     Ok(0)
 }
 
-pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
+pub fn q2(input: &str, _opts: &Options) -> DynResult<usize> {
     let input = munge_input!(input);
 
     let _ = input;
@@ -23,22 +27,30 @@ pub fn q2(input: &str, _args: &[&str]) -> DynResult<usize> {
     Ok(0)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generate a synthetic input with (roughly) `size` lines.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| rng.range(0..100)).join("\n")
+}
 
-    const EXAMPLE_1: &str = r#"
+/// Example inputs from the question description.
+pub const EXAMPLES: &[&str] = &[EXAMPLE_1];
+
+const EXAMPLE_1: &str = r#"
 .....dummy.....
 .....input.....
 "#;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn q1_e1() {
         let input = EXAMPLE_1;
         let expected = { 0 };
         let q = q1;
 
-        assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     }
 
     // #[test]
@@ -47,6 +59,6 @@ mod tests {
     //     let expected = { 0 };
     //     let q = q2;
 
-    //     assert_eq!(q(input.trim(), &[]).unwrap(), expected);
+    //     assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
     // }
 }
@@ -0,0 +1,695 @@
+//! An interactive debugger for day 8's handheld [`Vm`].
+//!
+//! Run with `cargo run -- debug 8` (which accepts the same `--input` /
+//! `--example` flags as running a day), then type `help` for a list of
+//! commands.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::io::{BufRead, Write};
+
+use crate::day8::{Instr, Vm};
+use crate::isa::{self, NUM_REGS};
+use crate::DynResult;
+
+/// How many previous states are kept around for reverse-stepping.
+pub const HISTORY_LEN: usize = 10_000;
+
+/// Most instructions `continue` will execute, in case the program never
+/// repeats a state (e.g: counting up forever).
+pub const CONTINUE_LIMIT: usize = 1 << 20;
+
+/// How many instructions `list` shows either side of the pc by default.
+const LIST_CONTEXT: usize = 3;
+
+const HELP: &str = "\
+commands:
+  s, step [n]          execute the next n (default 1) instructions
+  rs, rstep [n]        undo the last n (default 1) instructions
+  c, continue          run until a breakpoint, watchpoint, loop, or termination
+  b, break <pc>        toggle a breakpoint on pc
+  w, watch [value]     stop when acc changes (or when it equals value)
+  unwatch              remove all watchpoints
+  r, regs              show pc, acc, registers, and any queued port values
+  l, list [n]          show n (default 3) instructions either side of pc
+  reset                restart the program (keeping breakpoints)
+  h, help              show this message
+  q, quit              exit the debugger
+an empty line repeats the previous command";
+
+/// Stop execution based on the value of `acc`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Watchpoint {
+    /// Stop whenever `acc` changes.
+    Change,
+    /// Stop when `acc` becomes the given value.
+    Equals(i64),
+}
+
+/// Why execution stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stop {
+    /// Executed the requested number of steps.
+    Stepped,
+    Breakpoint(usize),
+    Watchpoint {
+        old: i64,
+        new: i64,
+    },
+    /// About to execute an instruction which has already been executed (from
+    /// the same machine state), so the program would loop forever.
+    Loop(usize),
+    /// Executed [`CONTINUE_LIMIT`] instructions without stopping.
+    StepLimit,
+    Terminated,
+    /// There's no more history to step back through.
+    HistoryStart,
+}
+
+impl core::fmt::Display for Stop {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Stop::Stepped => Ok(()),
+            Stop::Breakpoint(pc) => write!(f, "hit breakpoint at {}", pc),
+            Stop::Watchpoint { old, new } => write!(f, "acc changed: {} -> {}", old, new),
+            Stop::Loop(pc) => write!(f, "loop detected: {} was already executed", pc),
+            Stop::StepLimit => write!(f, "still running after {} steps", CONTINUE_LIMIT),
+            Stop::Terminated => write!(f, "program terminated"),
+            Stop::HistoryStart => write!(f, "reached the start of the history"),
+        }
+    }
+}
+
+/// A debugger command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Step(usize),
+    ReverseStep(usize),
+    Continue,
+    Break(usize),
+    Watch(Watchpoint),
+    Unwatch,
+    Regs,
+    List(usize),
+    Reset,
+    Help,
+    Quit,
+}
+
+impl std::str::FromStr for Command {
+    type Err = Box<dyn std::error::Error + Send + Sync>;
+    fn from_str(s: &str) -> DynResult<Command> {
+        let mut words = s.split_whitespace();
+        let cmd = words.next().ok_or("empty command")?;
+        let arg = words.next();
+        if words.next().is_some() {
+            return Err("too many arguments".into());
+        }
+
+        let count = |default| -> DynResult<usize> {
+            Ok(arg.map(str::parse).transpose()?.unwrap_or(default))
+        };
+
+        let cmd = match cmd {
+            "s" | "step" => Command::Step(count(1)?),
+            "rs" | "rstep" => Command::ReverseStep(count(1)?),
+            "c" | "continue" => Command::Continue,
+            "b" | "break" => Command::Break(arg.ok_or("missing pc")?.parse()?),
+            "w" | "watch" => Command::Watch(match arg {
+                Some(val) => Watchpoint::Equals(val.parse()?),
+                None => Watchpoint::Change,
+            }),
+            "unwatch" => Command::Unwatch,
+            "r" | "regs" => Command::Regs,
+            "l" | "list" => Command::List(count(LIST_CONTEXT)?),
+            "reset" => Command::Reset,
+            "h" | "help" => Command::Help,
+            "q" | "quit" => Command::Quit,
+            _ => return Err(format!("unknown command: {} (see `help`)", cmd).into()),
+        };
+        Ok(cmd)
+    }
+}
+
+/// Wraps a [`Vm`], keeping track of breakpoints, watchpoints, and the states
+/// it's been in.
+///
+/// ```
+/// use aoc20::day8::Vm;
+/// use aoc20::debugger::{Debugger, Stop};
+///
+/// let vm = Vm::new("nop +0\nacc +1\njmp -2").unwrap();
+/// let mut dbg = Debugger::new(vm);
+/// dbg.toggle_breakpoint(2);
+///
+/// assert_eq!(dbg.cont().unwrap(), Stop::Breakpoint(2));
+/// dbg.toggle_breakpoint(2);
+/// assert_eq!(dbg.cont().unwrap(), Stop::Loop(2));
+/// assert_eq!(dbg.vm.acc, 2);
+///
+/// dbg.step_back(2);
+/// assert_eq!((dbg.vm.pc, dbg.vm.acc), (0, 1));
+/// ```
+///
+/// Stepping back also drops entries from an in-memory [`Trace`], but entries
+/// which were already written out stay there (as with [`Vm::reset`]).
+///
+/// [`Trace`]: crate::trace::Trace
+pub struct Debugger {
+    pub vm: Vm,
+    pub breakpoints: BTreeSet<usize>,
+    pub watchpoints: BTreeSet<Watchpoint>,
+    /// Previous states, oldest first.
+    history: VecDeque<Snapshot>,
+}
+
+/// Everything needed to undo a single instruction.
+struct Snapshot {
+    pc: usize,
+    acc: i64,
+    regs: [i64; NUM_REGS],
+    /// `(port, values read from the front, number of values written to the
+    /// back)` for every port the instruction touched.
+    ports: Vec<(i64, Vec<i64>, usize)>,
+}
+
+/// Work out how each port changed between `before` and `after`.
+///
+/// Ports are queues, so this looks for the fewest values read from the front
+/// which explain the change. Anything else an instruction does to a port is
+/// still undone correctly, it just takes more space (in the worst case, every
+/// value is "read" and then "written" again).
+fn port_changes(
+    before: &HashMap<i64, VecDeque<i64>>,
+    after: &HashMap<i64, VecDeque<i64>>,
+) -> Vec<(i64, Vec<i64>, usize)> {
+    let empty = VecDeque::new();
+    let ports = before.keys().chain(after.keys()).collect::<BTreeSet<_>>();
+
+    let mut changes = Vec::new();
+    for &port in ports {
+        let b = before.get(&port).unwrap_or(&empty);
+        let a = after.get(&port).unwrap_or(&empty);
+        if a == b {
+            continue;
+        }
+        let read = (0..=b.len())
+            .find(|&k| b.len() - k <= a.len() && b.iter().skip(k).eq(a.iter().take(b.len() - k)))
+            .unwrap();
+        let written = a.len() - (b.len() - read);
+        changes.push((port, b.iter().take(read).copied().collect(), written));
+    }
+    changes
+}
+
+impl Debugger {
+    pub fn new(vm: Vm) -> Debugger {
+        Debugger {
+            vm,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Toggle a breakpoint, returning whether it's now set.
+    pub fn toggle_breakpoint(&mut self, pc: usize) -> bool {
+        if self.breakpoints.remove(&pc) {
+            false
+        } else {
+            self.breakpoints.insert(pc);
+            true
+        }
+    }
+
+    pub fn terminated(&self) -> bool {
+        self.vm.pc == self.vm.program.len()
+    }
+
+    /// Execute a single instruction, checking watchpoints afterwards.
+    fn cycle(&mut self) -> DynResult<Option<Stop>> {
+        if self.terminated() {
+            return Ok(Some(Stop::Terminated));
+        }
+
+        let (pc, old) = (self.vm.pc, self.vm.acc);
+        // only extension instructions touch ports, so don't bother copying
+        // them otherwise
+        let ports = match self.vm.program.get(pc) {
+            Some(Instr::Ext(..)) => Some(self.vm.ports.clone()),
+            _ => None,
+        };
+        let regs = self.vm.regs;
+
+        self.vm.cycle()?;
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(Snapshot {
+            pc,
+            acc: old,
+            regs,
+            ports: match ports {
+                Some(ports) => port_changes(&ports, &self.vm.ports),
+                None => Vec::new(),
+            },
+        });
+
+        let new = self.vm.acc;
+        let watched = self.watchpoints.iter().any(|w| match *w {
+            Watchpoint::Change => old != new,
+            Watchpoint::Equals(v) => old != new && new == v,
+        });
+        Ok(if watched {
+            Some(Stop::Watchpoint { old, new })
+        } else {
+            None
+        })
+    }
+
+    /// Execute (up to) `n` instructions.
+    pub fn step(&mut self, n: usize) -> DynResult<Stop> {
+        for _ in 0..n {
+            if let Some(stop) = self.cycle()? {
+                return Ok(stop);
+            }
+        }
+        Ok(Stop::Stepped)
+    }
+
+    /// Undo (up to) `n` instructions.
+    pub fn step_back(&mut self, n: usize) -> Stop {
+        for _ in 0..n {
+            let snapshot = match self.history.pop_back() {
+                Some(snapshot) => snapshot,
+                None => return Stop::HistoryStart,
+            };
+
+            self.vm.pc = snapshot.pc;
+            self.vm.acc = snapshot.acc;
+            self.vm.regs = snapshot.regs;
+            for (port, read, written) in snapshot.ports {
+                let queue = self.vm.ports.entry(port).or_default();
+                queue.truncate(queue.len() - written);
+                for v in read.into_iter().rev() {
+                    queue.push_front(v);
+                }
+            }
+            self.vm.steps -= 1;
+            if let Some(trace) = &mut self.vm.trace {
+                trace.pop();
+            }
+        }
+        Stop::Stepped
+    }
+
+    /// Run until something interesting happens. Breakpoints are only checked
+    /// _after_ the first instruction, so continuing from a breakpoint doesn't
+    /// immediately stop again.
+    pub fn cont(&mut self) -> DynResult<Stop> {
+        // with the handheld's instructions, control flow only depends on the
+        // pc, but extensions (e.g: `jnz`) can branch on the rest of the state
+        let extended = (self.vm.program.iter()).any(|instr| matches!(instr, Instr::Ext(..)));
+        // output queues only ever grow, so only ports which are read from count
+        let inputs = (self.vm.program.iter())
+            .filter_map(|instr| match *instr {
+                Instr::Ext(op, [port, _]) if core::ptr::eq(op, &isa::IN) => Some(port),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+
+        let mut seen = HashSet::new();
+        for _ in 0..CONTINUE_LIMIT {
+            let state = if extended {
+                Some(self.machine_state(&inputs))
+            } else {
+                None
+            };
+            if !seen.insert((self.vm.pc, state)) {
+                return Ok(Stop::Loop(self.vm.pc));
+            }
+            if let Some(stop) = self.cycle()? {
+                return Ok(stop);
+            }
+            if self.breakpoints.contains(&self.vm.pc) {
+                return Ok(Stop::Breakpoint(self.vm.pc));
+            }
+        }
+        Ok(Stop::StepLimit)
+    }
+
+    /// Everything besides the pc which can affect what the program does next,
+    /// given the ports it reads `inputs` from.
+    fn machine_state(&self, inputs: &BTreeSet<i64>) -> (i64, [i64; NUM_REGS], Vec<VecDeque<i64>>) {
+        // a missing port behaves the same as an empty one
+        let ports = (inputs.iter())
+            .map(|port| self.vm.ports.get(port).cloned().unwrap_or_default())
+            .collect();
+        (self.vm.acc, self.vm.regs, ports)
+    }
+
+    pub fn reset(&mut self) {
+        self.vm.reset();
+        self.history.clear();
+    }
+
+    /// List the instructions within `context` of the pc.
+    pub fn list(&self, context: usize, out: &mut impl Write) -> std::io::Result<()> {
+        let pc = self.vm.pc;
+        let start = pc.saturating_sub(context);
+        let end = (pc + context + 1).min(self.vm.program.len());
+        for i in start..end {
+            writeln!(
+                out,
+                "{}{} {:>4}: {}",
+                if i == pc { "=>" } else { "  " },
+                if self.breakpoints.contains(&i) {
+                    '*'
+                } else {
+                    ' '
+                },
+                i,
+                self.vm.program[i]
+            )?;
+        }
+        if pc == self.vm.program.len() {
+            writeln!(out, "=>  {:>4}: <end>", pc)?;
+        }
+        Ok(())
+    }
+
+    fn regs(&self, out: &mut impl Write) -> std::io::Result<()> {
+        write!(out, "pc={} acc={}", self.vm.pc, self.vm.acc)?;
+        for (i, v) in self.vm.regs.iter().enumerate() {
+            write!(out, " r{}={}", i, v)?;
+        }
+        writeln!(out)?;
+
+        let mut ports = (self.vm.ports.iter())
+            .filter(|(_, queue)| !queue.is_empty())
+            .collect::<Vec<_>>();
+        if !ports.is_empty() {
+            ports.sort_unstable_by_key(|&(&port, _)| port);
+            write!(out, "ports:")?;
+            for (port, queue) in ports {
+                write!(out, " {}={:?}", port, queue)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Execute a command, writing any output to `out`. Returns `false` once
+    /// the debugger should exit.
+    pub fn exec(&mut self, cmd: &Command, out: &mut impl Write) -> DynResult<bool> {
+        let stop = match *cmd {
+            Command::Step(n) => self.step(n)?,
+            Command::ReverseStep(n) => self.step_back(n),
+            Command::Continue => self.cont()?,
+            Command::Break(pc) => {
+                let set = self.toggle_breakpoint(pc);
+                writeln!(
+                    out,
+                    "breakpoint at {} {}",
+                    pc,
+                    if set { "set" } else { "removed" }
+                )?;
+                return Ok(true);
+            }
+            Command::Watch(w) => {
+                self.watchpoints.insert(w);
+                return Ok(true);
+            }
+            Command::Unwatch => {
+                self.watchpoints.clear();
+                return Ok(true);
+            }
+            Command::Regs => {
+                self.regs(out)?;
+                return Ok(true);
+            }
+            Command::List(n) => {
+                self.list(n, out)?;
+                return Ok(true);
+            }
+            Command::Reset => {
+                self.reset();
+                Stop::Stepped
+            }
+            Command::Help => {
+                writeln!(out, "{}", HELP)?;
+                return Ok(true);
+            }
+            Command::Quit => return Ok(false),
+        };
+
+        if stop != Stop::Stepped {
+            writeln!(out, "{}", stop)?;
+        }
+        self.regs(out)?;
+        self.list(0, out)?;
+        Ok(true)
+    }
+}
+
+/// Read commands from `input` until it runs out (or `quit`), writing output
+/// (and prompts) to `out`.
+pub fn repl(dbg: &mut Debugger, input: impl BufRead, out: &mut impl Write) -> DynResult<()> {
+    let mut prev = None;
+    let mut lines = input.lines();
+    loop {
+        write!(out, "(dbg) ")?;
+        out.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+
+        let cmd = match (line.trim(), prev.take()) {
+            ("", Some(cmd)) => cmd,
+            ("", None) => continue,
+            (line, _) => match line.parse::<Command>() {
+                Ok(cmd) => cmd,
+                Err(e) => {
+                    writeln!(out, "error: {}", e)?;
+                    continue;
+                }
+            },
+        };
+
+        match dbg.exec(&cmd, out) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => writeln!(out, "error: {}", e)?,
+        }
+        prev = Some(cmd);
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::isa::Isa;
+    use crate::trace::Trace;
+
+    fn debugger() -> Debugger {
+        let asm = crate::day8::EXAMPLES[0].trim();
+        Debugger::new(Vm::new(asm).unwrap())
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!("s".parse::<Command>().unwrap(), Command::Step(1));
+        assert_eq!(
+            "rstep 4".parse::<Command>().unwrap(),
+            Command::ReverseStep(4)
+        );
+        assert_eq!("b 3".parse::<Command>().unwrap(), Command::Break(3));
+        assert_eq!(
+            "w -2".parse::<Command>().unwrap(),
+            Command::Watch(Watchpoint::Equals(-2))
+        );
+        assert_eq!("list".parse::<Command>().unwrap(), Command::List(3));
+        assert!("b".parse::<Command>().is_err());
+        assert!("s 1 2".parse::<Command>().is_err());
+        assert!("jump".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn watchpoints() {
+        let mut dbg = debugger();
+        dbg.watchpoints.insert(Watchpoint::Change);
+        assert_eq!(dbg.cont().unwrap(), Stop::Watchpoint { old: 0, new: 1 });
+        assert_eq!(dbg.vm.pc, 2);
+
+        dbg.watchpoints.clear();
+        dbg.watchpoints.insert(Watchpoint::Equals(5));
+        assert_eq!(dbg.cont().unwrap(), Stop::Watchpoint { old: 2, new: 5 });
+
+        dbg.watchpoints.clear();
+        assert_eq!(dbg.cont().unwrap(), Stop::Loop(4));
+    }
+
+    #[test]
+    fn regs() {
+        let mut out = Vec::new();
+        debugger().exec(&Command::Regs, &mut out).unwrap();
+        assert_eq!(out, b"pc=0 acc=0 r0=0 r1=0 r2=0 r3=0\n");
+
+        let asm = "in +0\nst r1\nout +2\nset r3 -4\nhlt";
+        let mut dbg = Debugger::new(Vm::with_isa(asm, &Isa::extended()).unwrap());
+        dbg.vm.ports.insert(0, vec![3, 4].into());
+        dbg.step(4).unwrap();
+
+        let mut out = Vec::new();
+        dbg.exec(&Command::Regs, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "pc=4 acc=3 r0=0 r1=3 r2=0 r3=-4\nports: 0=[4] 2=[3]\n"
+        );
+    }
+
+    #[test]
+    fn continue_extensions() {
+        // revisits pc 1 on every pass, but with a different acc each time
+        let asm = "acc +3\nout +1\nacc -1\njnz -2";
+        let mut dbg = Debugger::new(Vm::with_isa(asm, &Isa::extended()).unwrap());
+        assert_eq!(dbg.cont().unwrap(), Stop::Terminated);
+        assert_eq!(dbg.vm.ports[&1], [3, 2, 1]);
+
+        // ...but going around with the exact same state is still a loop (pc 1
+        // is first revisited with r0 changed, so pc 2 is the first repeat)
+        let asm = "acc +1\nst r0\njnz -1";
+        let mut dbg = Debugger::new(Vm::with_isa(asm, &Isa::extended()).unwrap());
+        assert_eq!(dbg.cont().unwrap(), Stop::Loop(2));
+
+        // output doesn't affect what the program does next
+        let asm = "out +1\njmp -1";
+        let mut dbg = Debugger::new(Vm::with_isa(asm, &Isa::extended()).unwrap());
+        assert_eq!(dbg.cont().unwrap(), Stop::Loop(0));
+        assert_eq!(dbg.vm.ports[&1], [0]);
+
+        // ...whereas input does
+        let asm = "in +0\njnz -1\nhlt";
+        let mut dbg = Debugger::new(Vm::with_isa(asm, &Isa::extended()).unwrap());
+        dbg.vm.ports.insert(0, vec![3, 3, 0].into());
+        assert_eq!(dbg.cont().unwrap(), Stop::Terminated);
+
+        // states which never repeat are given up on eventually
+        let asm = "set r0 +0\nacc +1\njmp -1";
+        let mut dbg = Debugger::new(Vm::with_isa(asm, &Isa::extended()).unwrap());
+        assert_eq!(dbg.cont().unwrap(), Stop::StepLimit);
+    }
+
+    #[test]
+    fn reverse_step() {
+        let mut dbg = debugger();
+        assert_eq!(dbg.step(100).unwrap(), Stop::Stepped);
+        let (pc, acc) = (dbg.vm.pc, dbg.vm.acc);
+
+        assert_eq!(dbg.step_back(3), Stop::Stepped);
+        dbg.step(3).unwrap();
+        assert_eq!((dbg.vm.pc, dbg.vm.acc), (pc, acc));
+
+        assert_eq!(dbg.step_back(101), Stop::HistoryStart);
+        assert_eq!((dbg.vm.pc, dbg.vm.acc), (0, 0));
+
+        // old history is dropped
+        dbg.step(HISTORY_LEN + 10).unwrap();
+        assert_eq!(dbg.step_back(HISTORY_LEN + 1), Stop::HistoryStart);
+        assert_ne!(dbg.vm.pc, 0);
+    }
+
+    #[test]
+    fn reverse_step_extensions() {
+        let asm = "in +0\nst r1\nin +0\nadd r1\nout +1\nset r2 +7\nhlt";
+        let vm = Vm::with_isa(asm, &Isa::extended())
+            .unwrap()
+            .with_trace(Trace::ring(100));
+        let mut dbg = Debugger::new(vm);
+        dbg.vm.ports.insert(0, vec![3, 4, 5].into());
+
+        assert_eq!(dbg.step(100).unwrap(), Stop::Terminated);
+        assert_eq!(dbg.vm.ports[&0], [5]);
+        assert_eq!(dbg.vm.ports[&1], [7]);
+        assert_eq!(dbg.vm.regs, [0, 3, 7, 0]);
+
+        dbg.step_back(2);
+        assert_eq!(dbg.vm.pc, 5);
+        assert_eq!(dbg.vm.regs, [0, 3, 0, 0]);
+        dbg.step_back(1);
+        assert!(dbg.vm.ports[&1].is_empty());
+        dbg.step_back(2);
+        assert_eq!(dbg.vm.ports[&0], [4, 5]);
+        assert_eq!(dbg.vm.acc, 3);
+        let trace = dbg.vm.trace.as_ref().unwrap().entries().unwrap();
+        assert_eq!(trace.back().unwrap().pc, 1);
+
+        // replaying gives the same results
+        assert_eq!(dbg.step(100).unwrap(), Stop::Terminated);
+        assert_eq!(dbg.vm.ports[&0], [5]);
+        assert_eq!(dbg.vm.ports[&1], [7]);
+        assert_eq!(dbg.vm.regs, [0, 3, 7, 0]);
+        let trace = dbg.vm.trace.as_ref().unwrap().entries().unwrap();
+        assert_eq!(trace.len(), 7);
+
+        dbg.step_back(100);
+        assert_eq!(dbg.vm.ports[&0], [3, 4, 5]);
+        assert_eq!((dbg.vm.pc, dbg.vm.acc, dbg.vm.regs), (0, 0, [0; NUM_REGS]));
+    }
+
+    #[test]
+    fn port_diffs() {
+        let ports = |v: &[(i64, &[i64])]| {
+            v.iter()
+                .map(|&(p, q)| (p, q.iter().copied().collect()))
+                .collect::<HashMap<_, _>>()
+        };
+        let before = ports(&[(0, &[1, 2, 3]), (1, &[])]);
+        let after = ports(&[(0, &[2, 3, 4, 5]), (1, &[]), (2, &[9])]);
+        assert_eq!(
+            port_changes(&before, &after),
+            [(0, vec![1], 2), (2, vec![], 1)]
+        );
+
+        // not a queue operation, but still undoable
+        let after = ports(&[(0, &[2, 1]), (1, &[])]);
+        assert_eq!(port_changes(&before, &after), [(0, vec![1, 2, 3], 2)]);
+    }
+
+    #[test]
+    fn session() {
+        let mut dbg = debugger();
+        let mut out = Vec::new();
+        repl(
+            &mut dbg,
+            "b 4\nc\n\nb 4\nc\nbogus\nl 1\nq\ns\n".as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            out,
+            "\
+(dbg) breakpoint at 4 set
+(dbg) hit breakpoint at 4
+pc=4 acc=5 r0=0 r1=0 r2=0 r3=0
+=>*    4: jmp -3
+(dbg) hit breakpoint at 4
+pc=4 acc=10 r0=0 r1=0 r2=0 r3=0
+=>*    4: jmp -3
+(dbg) breakpoint at 4 removed
+(dbg) loop detected: 4 was already executed
+pc=4 acc=15 r0=0 r1=0 r2=0 r3=0
+=>     4: jmp -3
+(dbg) error: unknown command: bogus (see `help`)
+(dbg)        3: acc +3
+=>     4: jmp -3
+       5: acc -99
+(dbg) \n"
+        );
+    }
+}
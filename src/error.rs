@@ -0,0 +1,165 @@
+//! Errors which point at a specific location in a question's input.
+
+use std::fmt::Display;
+
+/// An error at a particular line and column of a question's input.
+///
+/// Rendered as a caret diagnostic:
+///
+/// ```text
+/// day 9, line 3, column 3: invalid digit found in string
+///   |
+/// 3 | 15x
+///   |   ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputError {
+    /// Filled in once the error bubbles up through [`Solution::part`].
+    ///
+    /// [`Solution::part`]: crate::solution::Solution::part
+    pub day: Option<usize>,
+    /// 1-indexed line number
+    pub line: usize,
+    /// 1-indexed column number (in chars)
+    pub col: usize,
+    /// Width of the offending span (in chars)
+    pub width: usize,
+    /// The full text of the offending line
+    pub snippet: String,
+    pub msg: String,
+}
+
+impl InputError {
+    /// Create an error pointing at `span`, which should be a substring of
+    /// `input` (e.g: a line or token produced by splitting the input).
+    ///
+    /// If `span` doesn't point into `input`, the first occurrence of `span`
+    /// in `input` is used instead.
+    pub fn new(input: &str, span: &str, msg: impl Display) -> InputError {
+        let (input_start, span_start) = (input.as_ptr() as usize, span.as_ptr() as usize);
+        let offset = if (input_start..=input_start + input.len()).contains(&span_start) {
+            span_start - input_start
+        } else {
+            input.find(span).unwrap_or(0)
+        };
+
+        let line_start = input[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = input[offset..]
+            .find('\n')
+            .map(|i| i + offset)
+            .unwrap_or_else(|| input.len());
+
+        let line = input[..offset].matches('\n').count() + 1;
+        let col = input[line_start..offset].chars().count() + 1;
+        let width = span
+            .chars()
+            .count()
+            .min(input[offset..line_end].chars().count())
+            .max(1);
+
+        InputError {
+            day: None,
+            line,
+            col,
+            width,
+            snippet: input[line_start..line_end].to_string(),
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl core::fmt::Display for InputError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {}, ", day)?;
+        }
+        writeln!(f, "line {}, column {}: {}", self.line, self.col, self.msg)?;
+
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.snippet)?;
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(self.col - 1),
+            "^".repeat(self.width)
+        )
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Attach an input location to a failed `Result`.
+///
+/// ```
+/// use aoc20::error::ResultExt;
+///
+/// let input = "35\n20\n15x";
+/// let ln = input.split('\n').last().unwrap();
+/// let err = ln.parse::<usize>().at(input, ln).unwrap_err();
+/// assert_eq!((err.line, err.col), (3, 1));
+/// ```
+pub trait ResultExt<T> {
+    /// Convert the error into an [`InputError`] pointing at `span` within
+    /// `input`.
+    fn at(self, input: &str, span: &str) -> Result<T, InputError>;
+}
+
+impl<T, E: Display> ResultExt<T> for Result<T, E> {
+    fn at(self, input: &str, span: &str) -> Result<T, InputError> {
+        self.map_err(|e| InputError::new(input, span, e))
+    }
+}
+
+/// Attach an input location to a missing `Option`.
+pub trait OptionExt<T> {
+    /// Convert `None` into an [`InputError`] pointing at `span` within `input`.
+    fn ok_or_at(self, input: &str, span: &str, msg: &str) -> Result<T, InputError>;
+}
+
+impl<T> OptionExt<T> for Option<T> {
+    fn ok_or_at(self, input: &str, span: &str, msg: &str) -> Result<T, InputError> {
+        self.ok_or_else(|| InputError::new(input, span, msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location() {
+        let input = "35\n20\n15x\n25";
+        let ln = input.split('\n').nth(2).unwrap();
+        let e = ln.parse::<usize>().at(input, ln).unwrap_err();
+        assert_eq!((e.line, e.col, e.width), (3, 1, 3));
+        assert_eq!(e.snippet, "15x");
+
+        let e = InputError::new(input, &ln[2..], "bad char");
+        assert_eq!((e.line, e.col, e.width), (3, 3, 1));
+    }
+
+    #[test]
+    fn render() {
+        let input = "nop +0\nacc +1\njmp +4\nfoo +3";
+        let ln = input.split('\n').nth(3).unwrap();
+        let mut e = InputError::new(input, &ln[..3], "invalid opcode");
+        e.day = Some(8);
+        assert_eq!(
+            e.to_string(),
+            "day 8, line 4, column 1: invalid opcode\n  |\n4 | foo +3\n  | ^^^"
+        );
+    }
+
+    #[test]
+    fn span_not_in_input() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg";
+        let owned = String::from("b: cdefg");
+        let e = InputError::new(input, &owned, "oops");
+        assert_eq!((e.line, e.col), (2, 5));
+
+        let e = InputError::new(input, "", "oops");
+        assert_eq!((e.line, e.col, e.width), (1, 1, 1));
+    }
+}
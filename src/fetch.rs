@@ -0,0 +1,295 @@
+//! Downloading (and caching) question inputs.
+//!
+//! Inputs are cached on disk as `<cache_dir>/<year>/<day>.txt`, and are only
+//! ever downloaded once. Downloading requires a session cookie, which is read
+//! from a file containing something like `session=53616c...`.
+//!
+//! Inputs cached before multiple years were supported (i.e: 2020's inputs, as
+//! `<cache_dir>/<day>.txt`) are still picked up.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::DynResult;
+
+/// Default URL inputs are fetched from (i.e:
+/// `<base_url>/<year>/day/<day>/input`).
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+/// Default file containing the session cookie.
+pub const DEFAULT_SESSION_FILE: &str = "./cookie.txt";
+/// Default folder inputs are cached in.
+pub const DEFAULT_CACHE_DIR: &str = "./inputs";
+/// Default minimum time between two consecutive downloads.
+pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// File in the cache dir used to track when the last download happened.
+const LAST_FETCH_FILE: &str = ".last_fetch";
+/// Year of the inputs which may be cached using the old `<day>.txt` layout.
+const LEGACY_YEAR: usize = 2020;
+
+pub struct Fetcher {
+    pub base_url: String,
+    pub session_file: PathBuf,
+    pub cache_dir: PathBuf,
+    pub min_interval: Duration,
+}
+
+impl Default for Fetcher {
+    fn default() -> Fetcher {
+        Fetcher {
+            base_url: DEFAULT_BASE_URL.into(),
+            session_file: DEFAULT_SESSION_FILE.into(),
+            cache_dir: DEFAULT_CACHE_DIR.into(),
+            min_interval: DEFAULT_MIN_INTERVAL,
+        }
+    }
+}
+
+impl Fetcher {
+    /// Construct a fetcher using the defaults, overridden by the `AOC_BASE_URL`
+    /// and `AOC_SESSION_FILE` environment variables (if set).
+    pub fn from_env() -> Fetcher {
+        let mut fetcher = Fetcher::default();
+        if let Ok(url) = std::env::var("AOC_BASE_URL") {
+            fetcher.base_url = url;
+        }
+        if let Ok(path) = std::env::var("AOC_SESSION_FILE") {
+            fetcher.session_file = path.into();
+        }
+        fetcher
+    }
+
+    /// Path to a day's cached input.
+    ///
+    /// This is `<cache_dir>/<year>/<day>.txt`, unless the input was cached
+    /// using the legacy layout (and hasn't been cached using the new one).
+    pub fn input_path(&self, year: usize, day: usize) -> PathBuf {
+        let path = self
+            .cache_dir
+            .join(year.to_string())
+            .join(format!("{}.txt", day));
+        if year == LEGACY_YEAR && !path.exists() {
+            let legacy_path = self.cache_dir.join(format!("{}.txt", day));
+            if legacy_path.exists() {
+                return legacy_path;
+            }
+        }
+        path
+    }
+
+    /// Load a day's input, downloading it if it hasn't been cached yet.
+    /// Trailing whitespace is stripped.
+    ///
+    /// Empty files (e.g: placeholders created when scaffolding a new day) are
+    /// treated as though they haven't been cached.
+    pub fn load(&self, year: usize, day: usize) -> DynResult<String> {
+        let input_path = self.input_path(year, day);
+
+        let cached = match std::fs::read_to_string(&input_path) {
+            Ok(input) => Some(input),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(
+                    format!("Could not open {}: {}", input_path.to_string_lossy(), e).into(),
+                )
+            }
+        };
+
+        let mut input = match cached.filter(|input| !input.trim().is_empty()) {
+            Some(input) => input,
+            None => {
+                let input = self.fetch(year, day).map_err(|e| {
+                    format!(
+                        "Could not open {}, and downloading it failed: {}",
+                        input_path.to_string_lossy(),
+                        e
+                    )
+                })?;
+                if let Some(dir) = input_path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                std::fs::write(&input_path, &input)?;
+                input
+            }
+        };
+
+        input.truncate(input.trim_end().len());
+        Ok(input)
+    }
+
+    /// Download a day's input, bypassing the cache.
+    pub fn fetch(&self, year: usize, day: usize) -> DynResult<String> {
+        let session = std::fs::read_to_string(&self.session_file).map_err(|e| {
+            format!(
+                "could not read session cookie from {}: {}",
+                self.session_file.to_string_lossy(),
+                e
+            )
+        })?;
+
+        self.wait_for_rate_limit()?;
+
+        let url = format!(
+            "{}/{}/day/{}/input",
+            self.base_url.trim_end_matches('/'),
+            year,
+            day
+        );
+        let resp = ureq::get(&url)
+            .set("Cookie", session.trim())
+            .set("User-Agent", "github.com/daniel5151/aoc20")
+            .timeout(Duration::from_secs(30))
+            .call();
+
+        if let Some(e) = resp.synthetic_error() {
+            return Err(format!("could not fetch {}: {}", url, e).into());
+        }
+
+        if !resp.ok() {
+            return Err(format!("could not fetch {}: HTTP {}", url, resp.status()).into());
+        }
+
+        Ok(resp.into_string()?)
+    }
+
+    /// Sleep until at least `min_interval` has passed since the last download.
+    fn wait_for_rate_limit(&self) -> DynResult<()> {
+        let last_fetch_path = self.cache_dir.join(LAST_FETCH_FILE);
+
+        let last_fetch = std::fs::metadata(&last_fetch_path).and_then(|m| m.modified());
+        if let Ok(last_fetch) = last_fetch {
+            let elapsed = SystemTime::now()
+                .duration_since(last_fetch)
+                .unwrap_or_default();
+            if let Some(remaining) = self.min_interval.checked_sub(elapsed) {
+                eprintln!("Rate limited: waiting {:?} before downloading", remaining);
+                std::thread::sleep(remaining);
+            }
+        }
+
+        std::fs::create_dir_all(&self.cache_dir)?;
+        touch(&last_fetch_path)
+    }
+}
+
+fn touch(path: &Path) -> DynResult<()> {
+    std::fs::write(path, "")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Spin up a local HTTP server which responds to a single request with the
+    /// given body, returning its base URL, and a handle which resolves to the
+    /// raw request.
+    fn stand_in_server(body: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut req = String::new();
+            loop {
+                let mut ln = String::new();
+                reader.read_line(&mut ln).unwrap();
+                if ln.trim().is_empty() {
+                    break;
+                }
+                req += &ln;
+            }
+
+            let mut stream = stream;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+
+            req
+        });
+
+        (base_url, handle)
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc20-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn fetch_and_cache() {
+        let dir = scratch_dir("fetch");
+        std::fs::write(dir.join("cookie.txt"), "session=1234\n").unwrap();
+
+        let (base_url, server) = stand_in_server("1721\n979\n");
+        let fetcher = Fetcher {
+            base_url,
+            session_file: dir.join("cookie.txt"),
+            cache_dir: dir.join("inputs"),
+            min_interval: Duration::default(),
+        };
+
+        assert_eq!(fetcher.load(2020, 1).unwrap(), "1721\n979");
+        assert!(dir.join("inputs/2020/1.txt").exists());
+
+        let req = server.join().unwrap();
+        assert!(req.starts_with("GET /2020/day/1/input HTTP/1.1"));
+        assert!(req.contains("Cookie: session=1234"));
+
+        // the server only responds once, so this must hit the cache
+        assert_eq!(fetcher.load(2020, 1).unwrap(), "1721\n979");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_session() {
+        let dir = scratch_dir("no-session");
+        let fetcher = Fetcher {
+            base_url: "http://127.0.0.1:1".into(),
+            session_file: dir.join("cookie.txt"),
+            cache_dir: dir.join("inputs"),
+            min_interval: Duration::default(),
+        };
+
+        assert!(fetcher.load(2020, 1).is_err());
+        assert!(!fetcher.input_path(2020, 1).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn legacy_layout() {
+        let dir = scratch_dir("legacy");
+        std::fs::create_dir_all(dir.join("inputs/2020")).unwrap();
+        std::fs::write(dir.join("inputs/5.txt"), "FBFBBFFRLR\n").unwrap();
+        std::fs::write(dir.join("inputs/2020/6.txt"), "abc\n").unwrap();
+        std::fs::write(dir.join("inputs/6.txt"), "stale\n").unwrap();
+        let fetcher = Fetcher {
+            base_url: "http://127.0.0.1:1".into(),
+            session_file: dir.join("cookie.txt"),
+            cache_dir: dir.join("inputs"),
+            min_interval: Duration::default(),
+        };
+
+        assert_eq!(fetcher.load(2020, 5).unwrap(), "FBFBBFFRLR");
+        // the new layout takes precedence
+        assert_eq!(fetcher.load(2020, 6).unwrap(), "abc");
+        // ...and the legacy layout only ever held 2020's inputs
+        assert_eq!(fetcher.input_path(2019, 5), dir.join("inputs/2019/5.txt"));
+        assert!(fetcher.load(2019, 5).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
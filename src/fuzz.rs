@@ -0,0 +1,113 @@
+//! Entry points for fuzzing solutions with arbitrary input.
+//!
+//! Solutions should never panic, no matter how malformed their input is. The
+//! targets under `fuzz/` (run with `cargo fuzz run day8`) feed arbitrary bytes
+//! to [`run_day`], and a (much) cheaper smoke test runs as part of the regular
+//! test suite.
+
+use crate::options::Options;
+use crate::solution::{self, Solution};
+
+/// `(day, part)`s which are skipped, since they take too long to be fuzzed
+/// meaningfully (e.g: day 15 part 2 plays 30 million turns no matter what).
+pub const SKIP: &[(usize, usize)] = &[(15, 2), (23, 2)];
+
+/// Run both parts of a solution on some arbitrary input, discarding the
+/// results. Input which isn't valid UTF-8 is ignored.
+pub fn run(sol: &dyn Solution, data: &[u8]) {
+    let input = match std::str::from_utf8(data) {
+        Ok(input) => input,
+        Err(_) => return,
+    };
+
+    let opts = Options::default();
+    for part in 1..=2 {
+        if SKIP.contains(&(sol.day(), part)) {
+            continue;
+        }
+        let _ = sol.part(part, input, &opts);
+    }
+}
+
+/// [`run`] the solution for a particular day. Does nothing if there's no
+/// solution for that day.
+pub fn run_day(year: usize, day: usize, data: &[u8]) {
+    if let Some(sol) = solution::find(year, day) {
+        run(sol, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use crate::util::rng::Rng;
+
+    /// Randomly corrupt a (known good) input.
+    fn mutate(rng: &mut Rng, input: &str) -> Vec<u8> {
+        const INTERESTING: &[u8] = b"\n\n ,:-+*()[]#.x0123456789";
+        const BIG: &[&str] = &["0", "00", "-1", "4294967296", "18446744073709551615"];
+
+        let mut data = input.as_bytes().to_vec();
+        for _ in 0..rng.range(1..4) {
+            let i = rng.range(0..data.len() + 1);
+            match rng.range(0..5) {
+                0 if i < data.len() => data[i] = *rng.choose(INTERESTING),
+                1 if i < data.len() => {
+                    data.remove(i);
+                }
+                2 => {
+                    let end = rng.range(i..data.len() + 1);
+                    let dup = data[i..end].to_vec();
+                    data.splice(i..i, dup);
+                }
+                3 => {
+                    let end = rng.range(i..data.len() + 1);
+                    data.truncate(end);
+                }
+                _ => {
+                    let big = rng.choose(BIG).bytes();
+                    data.splice(i..i, big);
+                }
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn smoke() {
+        // inputs which have tripped up solutions in the past
+        const REGRESSIONS: &[(usize, &str)] = &[(13, "939\n7,13,x,00,x,x,59,x,31,19")];
+        for &(day, input) in REGRESSIONS {
+            let sol = solution::find(2020, day).unwrap();
+            if catch_unwind(AssertUnwindSafe(|| run(sol, input.as_bytes()))).is_err() {
+                panic!("day {} panicked on input:\n{}", day, input);
+            }
+        }
+
+        let mut rng = Rng::new(0);
+        for sol in crate::SOLUTIONS {
+            let mut seeds = sol
+                .examples()
+                .iter()
+                .map(|e| e.trim().to_string())
+                .collect::<Vec<_>>();
+            seeds.push(sol.generate(0, 5));
+
+            for seed in &seeds {
+                for _ in 0..20 {
+                    let data = mutate(&mut rng, seed);
+                    if catch_unwind(AssertUnwindSafe(|| run(*sol, &data))).is_err() {
+                        panic!(
+                            "day {} panicked on input:\n{}",
+                            sol.day(),
+                            String::from_utf8_lossy(&data)
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Where a solution's input comes from.
+
+use std::io::Read;
+use std::path::PathBuf;
+
+use crate::fetch::Fetcher;
+use crate::solution::Solution;
+use crate::DynResult;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The day's (possibly downloaded) input in `inputs/<year>/`.
+    Default,
+    /// An explicit path on disk.
+    Path(PathBuf),
+    /// Read input from stdin.
+    Stdin,
+    /// One of the day's examples (1-indexed, in the order they're declared).
+    Example(usize),
+}
+
+impl Source {
+    /// Create a source from the values passed to `--input` and `--example`.
+    ///
+    /// `--input -` reads from stdin.
+    pub fn from_flags(input: Option<&str>, example: Option<&str>) -> DynResult<Source> {
+        let source = match (input, example) {
+            (Some(_), Some(_)) => return Err("cannot use both --input and --example".into()),
+            (None, None) => Source::Default,
+            (Some("-"), None) => Source::Stdin,
+            (Some(path), None) => Source::Path(path.into()),
+            (None, Some(n)) => match n.parse::<usize>() {
+                Ok(n) if n > 0 => Source::Example(n),
+                _ => return Err(format!("invalid example number: {}", n).into()),
+            },
+        };
+        Ok(source)
+    }
+
+    /// Load input for the given solution, stripping trailing whitespace (and
+    /// in the case of examples, leading whitespace as well).
+    pub fn load(&self, sol: &dyn Solution, fetcher: &Fetcher) -> DynResult<String> {
+        let mut input = match self {
+            Source::Default => return fetcher.load(sol.year(), sol.day()),
+            Source::Path(path) => std::fs::read_to_string(path)
+                .map_err(|e| format!("Could not open {}: {}", path.to_string_lossy(), e))?,
+            Source::Stdin => {
+                let mut input = String::new();
+                std::io::stdin().read_to_string(&mut input)?;
+                input
+            }
+            Source::Example(n) => {
+                let examples = sol.examples();
+                let example = examples.get(n - 1).ok_or_else(|| {
+                    format!("day {} only has {} example(s)", sol.day(), examples.len())
+                })?;
+                return Ok(example.trim().to_string());
+            }
+        };
+
+        input.truncate(input.trim_end().len());
+        Ok(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_flags() {
+        assert_eq!(Source::from_flags(None, None).unwrap(), Source::Default);
+        assert_eq!(Source::from_flags(Some("-"), None).unwrap(), Source::Stdin);
+        assert_eq!(
+            Source::from_flags(Some("alt.txt"), None).unwrap(),
+            Source::Path("alt.txt".into())
+        );
+        assert_eq!(
+            Source::from_flags(None, Some("2")).unwrap(),
+            Source::Example(2)
+        );
+        assert!(Source::from_flags(None, Some("0")).is_err());
+        assert!(Source::from_flags(Some("-"), Some("1")).is_err());
+    }
+
+    #[test]
+    fn examples() {
+        let sol = crate::solution::find(2020, 10).unwrap();
+        let input = Source::Example(1).load(sol, &Fetcher::default()).unwrap();
+        assert!(input.starts_with("16\n10"));
+        assert!(Source::Example(3).load(sol, &Fetcher::default()).is_err());
+    }
+}
@@ -0,0 +1,456 @@
+//! Pluggable instruction sets for day 8's handheld [`Vm`].
+//!
+//! Every opcode is described by an [`OpDef`], and an [`Isa`] is the set of
+//! opcodes a particular console understands. Parsing, encoding, and execution
+//! are all driven by these definitions, so variants of the console just need
+//! to register a few more opcodes:
+//!
+//! ```
+//! use aoc20::day8::Vm;
+//! use aoc20::isa::{self, Isa};
+//!
+//! let mut isa = Isa::handheld();
+//! isa.register(&isa::JNZ).unwrap();
+//! isa.register(&isa::OUT).unwrap();
+//!
+//! // count down from 3, sending each value to port 1
+//! let mut vm = Vm::with_isa("acc +3\nout +1\nacc -1\njnz -2", &isa).unwrap();
+//! assert!(vm.run(100).unwrap());
+//! assert_eq!(vm.ports[&1], [3, 2, 1]);
+//! ```
+//!
+//! [`Vm`]: crate::day8::Vm
+
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::day8::{Instr, InstrParseError, Vm};
+use crate::DynResult;
+
+/// Most operands any instruction can have.
+pub const MAX_ARGS: usize = 2;
+
+/// Number of general purpose registers (`r0` to `r3`), in addition to `acc`.
+pub const NUM_REGS: usize = 4;
+
+/// The kinds of operand an instruction can take.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Operand {
+    /// A signed integer, written with an explicit sign (e.g: `+3`).
+    Imm,
+    /// Like [`Operand::Imm`], but relative to the instruction's pc (so the
+    /// assembler accepts labels).
+    Offset,
+    /// A general purpose register (e.g: `r2`).
+    Reg,
+}
+
+impl Operand {
+    fn parse(self, s: &str) -> Result<i64, InstrParseError> {
+        match self {
+            Operand::Imm | Operand::Offset => s.parse().map_err(InstrParseError::InvalidData),
+            Operand::Reg => s
+                .strip_prefix('r')
+                .and_then(|r| r.parse::<usize>().ok())
+                .filter(|&r| r < NUM_REGS)
+                .map(|r| r as i64)
+                .ok_or_else(|| InstrParseError::InvalidRegister(s.into())),
+        }
+    }
+
+    fn fmt(self, f: &mut fmt::Formatter<'_>, val: i64) -> fmt::Result {
+        match self {
+            Operand::Imm | Operand::Offset => write!(f, "{:+}", val),
+            Operand::Reg => write!(f, "r{}", val),
+        }
+    }
+
+    /// Whether `val` is valid for this kind of operand (e.g: after decoding).
+    fn is_valid(self, val: i64) -> bool {
+        match self {
+            Operand::Imm | Operand::Offset => true,
+            Operand::Reg => (0..NUM_REGS as i64).contains(&val),
+        }
+    }
+}
+
+/// Where execution continues after an instruction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Flow {
+    /// The next instruction.
+    Next,
+    /// Jump relative to the current instruction.
+    Jump(i64),
+    /// Stop, as though the program had run off the end.
+    Halt,
+}
+
+/// The definition of an opcode.
+///
+/// Definitions are compared by address, so they should live in `static`s.
+pub struct OpDef {
+    pub name: &'static str,
+    /// Used to identify the opcode in encoded programs.
+    pub code: u8,
+    pub operands: &'static [Operand],
+    /// Execute the instruction. `args` holds the operands, in order (with
+    /// registers given by index), followed by zeroes.
+    pub exec: fn(vm: &mut Vm, args: [i64; MAX_ARGS]) -> DynResult<Flow>,
+}
+
+impl OpDef {
+    /// Create an instruction with the given (already parsed) operands.
+    pub fn instr(&'static self, args: [i64; MAX_ARGS]) -> Instr {
+        if core::ptr::eq(self, &NOP) {
+            Instr::Nop(args[0])
+        } else if core::ptr::eq(self, &ACC) {
+            Instr::Acc(args[0])
+        } else if core::ptr::eq(self, &JMP) {
+            Instr::Jmp(args[0])
+        } else {
+            Instr::Ext(self, args)
+        }
+    }
+
+    /// Parse the operands following the opcode.
+    pub fn parse<'a>(
+        &'static self,
+        mut operands: impl Iterator<Item = &'a str>,
+    ) -> Result<Instr, InstrParseError> {
+        let mut args = [0; MAX_ARGS];
+        for (arg, kind) in args.iter_mut().zip(self.operands) {
+            let s = operands.next().ok_or(InstrParseError::MissingData)?;
+            *arg = kind.parse(s)?;
+        }
+        if let Some(extra) = operands.next() {
+            return Err(InstrParseError::UnexpectedData(extra.into()));
+        }
+        Ok(self.instr(args))
+    }
+
+    /// Create an instruction from decoded operands, checking they're valid.
+    pub fn decode(&'static self, args: [i64; MAX_ARGS]) -> DynResult<Instr> {
+        for (&arg, kind) in args.iter().zip(self.operands) {
+            if !kind.is_valid(arg) {
+                return Err(
+                    format!("invalid {:?} operand for {}: {}", kind, self.name, arg).into(),
+                );
+            }
+        }
+        Ok(self.instr(args))
+    }
+
+    /// Format an instruction using this opcode.
+    pub fn fmt(&self, f: &mut fmt::Formatter<'_>, args: &[i64; MAX_ARGS]) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        for (&arg, kind) in args.iter().zip(self.operands) {
+            write!(f, " ")?;
+            kind.fmt(f, arg)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for OpDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OpDef({})", self.name)
+    }
+}
+
+impl PartialEq for OpDef {
+    fn eq(&self, other: &OpDef) -> bool {
+        core::ptr::eq(self, other)
+    }
+}
+
+impl Eq for OpDef {}
+
+/// The set of opcodes understood by a console.
+#[derive(Debug, Clone)]
+pub struct Isa {
+    ops: Cow<'static, [&'static OpDef]>,
+}
+
+impl Isa {
+    /// The puzzle's instruction set: `nop`, `acc`, and `jmp`.
+    pub fn handheld() -> Isa {
+        Isa {
+            ops: Cow::Borrowed(HANDHELD),
+        }
+    }
+
+    /// The puzzle's instruction set, plus every extension in this module.
+    pub fn extended() -> Isa {
+        let mut isa = Isa::handheld();
+        for op in EXTENSIONS {
+            isa.register(op).unwrap();
+        }
+        isa
+    }
+
+    /// Add an opcode. Names and codes must be unique.
+    pub fn register(&mut self, op: &'static OpDef) -> DynResult<()> {
+        if let Some(other) = self
+            .ops
+            .iter()
+            .find(|o| o.name == op.name || o.code == op.code)
+        {
+            return Err(format!(
+                "{} (code {}) conflicts with {} (code {})",
+                op.name, op.code, other.name, other.code
+            )
+            .into());
+        }
+        self.ops.to_mut().push(op);
+        Ok(())
+    }
+
+    pub fn ops(&self) -> &[&'static OpDef] {
+        &self.ops
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&'static OpDef> {
+        self.ops.iter().copied().find(|op| op.name == name)
+    }
+
+    pub fn by_code(&self, code: u8) -> Option<&'static OpDef> {
+        self.ops.iter().copied().find(|op| op.code == code)
+    }
+
+    /// Parse an instruction, written as its opcode followed by its operands
+    /// (separated by single spaces).
+    pub fn parse(&self, asm: &str) -> Result<Instr, InstrParseError> {
+        let mut words = asm.split(' ');
+        let name = words
+            .next()
+            .filter(|w| !w.is_empty())
+            .ok_or(InstrParseError::MissingOpcode)?;
+        let op = self
+            .by_name(name)
+            .ok_or_else(|| InstrParseError::InvalidOpcode(name.into()))?;
+        op.parse(words)
+    }
+}
+
+static HANDHELD: &[&OpDef] = &[&NOP, &ACC, &JMP];
+
+/// Every opcode (beyond the puzzle's) defined in this module.
+pub static EXTENSIONS: &[&OpDef] = &[&HLT, &JZ, &JNZ, &SET, &LD, &ST, &ADD, &OUT, &IN];
+
+fn reg(vm: &mut Vm, r: i64) -> DynResult<&mut i64> {
+    Ok(vm
+        .regs
+        .get_mut(r as usize)
+        .ok_or_else(|| format!("invalid register: r{}", r))?)
+}
+
+fn add(a: i64, b: i64) -> DynResult<i64> {
+    Ok(a.checked_add(b).ok_or("acc overflowed")?)
+}
+
+pub static NOP: OpDef = OpDef {
+    name: "nop",
+    code: 0,
+    // it doesn't go anywhere, but flipping it into a `jmp` would
+    operands: &[Operand::Offset],
+    exec: |_, _| Ok(Flow::Next),
+};
+
+pub static ACC: OpDef = OpDef {
+    name: "acc",
+    code: 1,
+    operands: &[Operand::Imm],
+    exec: |vm, [n, _]| {
+        vm.acc = add(vm.acc, n)?;
+        Ok(Flow::Next)
+    },
+};
+
+pub static JMP: OpDef = OpDef {
+    name: "jmp",
+    code: 2,
+    operands: &[Operand::Offset],
+    exec: |_, [offset, _]| Ok(Flow::Jump(offset)),
+};
+
+/// Stop the program.
+pub static HLT: OpDef = OpDef {
+    name: "hlt",
+    code: 3,
+    operands: &[],
+    exec: |_, _| Ok(Flow::Halt),
+};
+
+/// Jump if `acc` is zero.
+pub static JZ: OpDef = OpDef {
+    name: "jz",
+    code: 4,
+    operands: &[Operand::Offset],
+    exec: |vm, [offset, _]| {
+        Ok(if vm.acc == 0 {
+            Flow::Jump(offset)
+        } else {
+            Flow::Next
+        })
+    },
+};
+
+/// Jump if `acc` isn't zero.
+pub static JNZ: OpDef = OpDef {
+    name: "jnz",
+    code: 5,
+    operands: &[Operand::Offset],
+    exec: |vm, [offset, _]| {
+        Ok(if vm.acc != 0 {
+            Flow::Jump(offset)
+        } else {
+            Flow::Next
+        })
+    },
+};
+
+/// Set a register to a value.
+pub static SET: OpDef = OpDef {
+    name: "set",
+    code: 6,
+    operands: &[Operand::Reg, Operand::Imm],
+    exec: |vm, [r, n]| {
+        *reg(vm, r)? = n;
+        Ok(Flow::Next)
+    },
+};
+
+/// Load a register into `acc`.
+pub static LD: OpDef = OpDef {
+    name: "ld",
+    code: 7,
+    operands: &[Operand::Reg],
+    exec: |vm, [r, _]| {
+        vm.acc = *reg(vm, r)?;
+        Ok(Flow::Next)
+    },
+};
+
+/// Store `acc` in a register.
+pub static ST: OpDef = OpDef {
+    name: "st",
+    code: 8,
+    operands: &[Operand::Reg],
+    exec: |vm, [r, _]| {
+        *reg(vm, r)? = vm.acc;
+        Ok(Flow::Next)
+    },
+};
+
+/// Add a register to `acc`.
+pub static ADD: OpDef = OpDef {
+    name: "add",
+    code: 9,
+    operands: &[Operand::Reg],
+    exec: |vm, [r, _]| {
+        vm.acc = add(vm.acc, *reg(vm, r)?)?;
+        Ok(Flow::Next)
+    },
+};
+
+/// Send `acc` to an I/O port.
+pub static OUT: OpDef = OpDef {
+    name: "out",
+    code: 10,
+    operands: &[Operand::Imm],
+    exec: |vm, [port, _]| {
+        vm.ports.entry(port).or_default().push_back(vm.acc);
+        Ok(Flow::Next)
+    },
+};
+
+/// Receive a value from an I/O port into `acc`.
+pub static IN: OpDef = OpDef {
+    name: "in",
+    code: 11,
+    operands: &[Operand::Imm],
+    exec: |vm, [port, _]| {
+        let queue = vm.ports.get_mut(&port);
+        vm.acc = queue
+            .and_then(|q| q.pop_front())
+            .ok_or_else(|| format!("nothing to read from port {}", port))?;
+        Ok(Flow::Next)
+    },
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        let mut isa = Isa::handheld();
+        assert_eq!(isa.ops().len(), 3);
+        assert!(isa.parse("hlt").is_err());
+
+        isa.register(&HLT).unwrap();
+        assert_eq!(isa.parse("hlt").unwrap(), Instr::Ext(&HLT, [0, 0]));
+        assert!(isa.register(&HLT).is_err());
+
+        static CONFLICT: OpDef = OpDef {
+            name: "halt",
+            code: 3,
+            operands: &[],
+            exec: |_, _| Ok(Flow::Halt),
+        };
+        assert!(isa.register(&CONFLICT).is_err());
+
+        // the base instructions are still parsed into their own variants
+        assert_eq!(isa.parse("jmp -4").unwrap(), Instr::Jmp(-4));
+        assert_eq!(Isa::extended().ops().len(), 3 + EXTENSIONS.len());
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let isa = Isa::extended();
+        for asm in &["set r3 -7", "ld r0", "hlt", "jz +2", "in +1", "acc +0"] {
+            assert_eq!(isa.parse(asm).unwrap().to_string(), *asm);
+        }
+
+        let err = |asm| isa.parse(asm).unwrap_err().to_string();
+        assert_eq!(err("set r4 +1"), "invalid register: r4");
+        assert_eq!(err("set r1"), "missing data");
+        assert_eq!(err("hlt +1"), "unexpected data: +1");
+        assert_eq!(err("ld acc"), "invalid register: acc");
+        assert_eq!(err(""), "missing opcode");
+    }
+
+    #[test]
+    fn extensions() {
+        let isa = Isa::extended();
+        // multiply r0 by r1 using repeated addition, outputting each partial
+        // sum, then halt (skipping the infinite loop at the end)
+        let asm = "\
+set r0 +6
+set r1 +7
+ld r1
+jz +9
+acc -1
+st r1
+ld r2
+add r0
+st r2
+out +0
+ld r1
+jmp -9
+in +5
+hlt
+jmp +0";
+        let mut vm = Vm::with_isa(asm, &isa).unwrap();
+        vm.ports.entry(5).or_default().push_back(42);
+        assert!(vm.run(1000).unwrap());
+        assert_eq!(vm.regs[2], 42);
+        assert_eq!(vm.ports[&0], [6, 12, 18, 24, 30, 36, 42]);
+        assert_eq!(vm.acc, 42);
+        assert_eq!(vm.pc, vm.program.len());
+
+        // reading from an empty port is an error
+        vm.reset();
+        assert!(vm.run(1000).is_err());
+    }
+}
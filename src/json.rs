@@ -0,0 +1,136 @@
+//! Machine-readable output, as JSON lines (i.e: one object per run).
+//!
+//! ```text
+//! {"year":2020,"day":1,"part":1,"title":"Report Repair","answer":514579,"parse_ns":41200,"solve_ns":3100,"alloc":null,"error":null}
+//! {"year":2020,"day":9,"part":1,"title":"Encoding Error","answer":null,"parse_ns":0,"solve_ns":8100,"alloc":null,"error":{"message":"invalid digit found in string","line":3,"column":1}}
+//! ```
+
+use crate::error::InputError;
+use crate::runner::Run;
+use crate::solution::Answer;
+
+/// Serialize a run as a single-line JSON object.
+///
+/// Numeric answers are emitted as JSON numbers, and everything else as
+/// strings. Errors include their line and column if they were caused by
+/// malformed input. `alloc` is `null` unless the `alloc-stats` feature is
+/// enabled.
+pub fn run_to_json(run: &Run) -> String {
+    let (answer, error) = match &run.answer {
+        Ok(Answer::Int(n)) => (n.to_string(), "null".to_string()),
+        Ok(Answer::Str(s)) => (escape(s), "null".to_string()),
+        Err(e) => {
+            let error = match e.downcast_ref::<InputError>() {
+                Some(e) => format!(
+                    r#"{{"message":{},"line":{},"column":{}}}"#,
+                    escape(&e.msg),
+                    e.line,
+                    e.col
+                ),
+                None => format!(r#"{{"message":{}}}"#, escape(&e.to_string())),
+            };
+            ("null".to_string(), error)
+        }
+    };
+
+    let alloc = match run.alloc {
+        Some(a) => format!(
+            r#"{{"allocs":{},"bytes":{},"peak_bytes":{}}}"#,
+            a.allocs, a.bytes, a.peak
+        ),
+        None => "null".to_string(),
+    };
+
+    format!(
+        r#"{{"year":{},"day":{},"part":{},"title":{},"answer":{},"parse_ns":{},"solve_ns":{},"alloc":{},"error":{}}}"#,
+        run.year,
+        run.day,
+        run.part,
+        escape(run.title),
+        answer,
+        run.parse.as_nanos(),
+        run.solve.as_nanos(),
+        alloc,
+        error
+    )
+}
+
+/// Print each run on its own line.
+pub fn print_lines(runs: &[Run]) {
+    for run in runs {
+        println!("{}", run_to_json(run));
+    }
+}
+
+/// Quote and escape a string.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str(r#"\""#),
+            '\\' => out.push_str(r"\\"),
+            '\n' => out.push_str(r"\n"),
+            '\r' => out.push_str(r"\r"),
+            '\t' => out.push_str(r"\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!(r"\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    fn mk_run(answer: crate::DynResult<Answer>) -> Run {
+        Run {
+            year: 2020,
+            day: 21,
+            part: 2,
+            title: "Allergen Assessment",
+            answer,
+            parse: Duration::from_nanos(1500),
+            solve: Duration::from_micros(2),
+            alloc: None,
+        }
+    }
+
+    #[test]
+    fn answers() {
+        assert_eq!(
+            run_to_json(&mk_run(Ok(Answer::Int(-5)))),
+            r#"{"year":2020,"day":21,"part":2,"title":"Allergen Assessment","answer":-5,"parse_ns":1500,"solve_ns":2000,"alloc":null,"error":null}"#
+        );
+        assert!(run_to_json(&mk_run(Ok("a,\"b\"".into()))).contains(r#""answer":"a,\"b\"""#));
+
+        let mut run = mk_run(Ok(Answer::Int(1)));
+        run.alloc = Some(crate::alloc::AllocStats {
+            allocs: 3,
+            bytes: 96,
+            peak: 64,
+        });
+        assert!(run_to_json(&run).contains(r#""alloc":{"allocs":3,"bytes":96,"peak_bytes":64}"#));
+    }
+
+    #[test]
+    fn errors() {
+        let json = run_to_json(&mk_run(Err("no\nanswer".into())));
+        assert!(json.contains(r#""answer":null"#));
+        assert!(json.contains(r#""error":{"message":"no\nanswer"}"#));
+
+        let input = "mxmxvkd (contains dairy";
+        let e = InputError::new(input, &input[8..], "malformed allergens");
+        let json = run_to_json(&mk_run(Err(e.into())));
+        assert!(json.contains(r#""error":{"message":"malformed allergens","line":1,"column":9}"#));
+    }
+
+    #[test]
+    fn control_chars() {
+        assert_eq!(escape("\t\u{1}"), r#""\t\u0001""#);
+    }
+}
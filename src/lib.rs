@@ -0,0 +1,147 @@
+//! Solutions to Advent of Code 2020, along with the scaffolding used to run,
+//! verify, and benchmark them (which isn't tied to any particular year).
+//!
+//! Every solution is registered in [`SOLUTIONS`] (keyed by year and day), and
+//! implements the common [`Solution`] interface:
+//!
+//! ```
+//! use aoc20::{solution, Options};
+//!
+//! let sol = solution::find(2020, 1).unwrap();
+//! assert_eq!(sol.title(), "Report Repair");
+//!
+//! let input = sol.examples()[0].trim();
+//! let answer = sol.part(1, input, &Options::default()).unwrap();
+//! assert_eq!(answer.to_string(), "514579");
+//! ```
+//!
+//! The shared utilities (e.g: [`util::gcd_lcm`]) and individual days' internals
+//! (e.g: [`day8::Vm`]) can also be used directly.
+
+#![allow(clippy::unreadable_literal, clippy::type_complexity)]
+#![feature(
+    min_const_generics,
+    array_value_iter,
+    array_windows,
+    const_in_array_repeat_expressions
+)]
+
+#[macro_use]
+pub mod prelude;
+pub mod alloc;
+pub mod answers;
+pub mod asm;
+pub mod bench;
+pub mod cfg;
+pub mod debugger;
+pub mod error;
+pub mod fetch;
+pub mod fuzz;
+pub mod input;
+pub mod isa;
+pub mod json;
+pub mod options;
+pub mod runner;
+pub mod scaffold;
+pub mod solution;
+pub mod trace;
+pub mod util;
+pub mod watch;
+
+pub use options::{Opt, Options};
+pub use solution::{Answer, Day, Solution};
+
+/// Catch-all error type (works with anything that implements std::error::Error)
+pub type DynResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+// Utility macro to make adding new days a breeze
+//
+// Days are grouped by year. 2020's days live at the crate root (e.g:
+// `src/day1.rs`), whereas other years live in their own module (e.g: `2019 in
+// y2019 => { ... }` for `src/y2019/day1.rs`).
+macro_rules! days {
+    ($($year:literal $(in $module:ident)? => {
+        $($num:literal: $day:ident => $title:literal),* $(,)*
+    }),* $(,)*) => {
+        $(days!(@year $year [$($module)?] { $($num: $day => $title),* });)*
+
+        /// Every registered solution, ordered by year, then by day.
+        pub static SOLUTIONS: &[&dyn Solution] = &[$($(&Day::<$year, $num>),*),*];
+    };
+
+    (@year $year:literal [] { $($num:literal: $day:ident => $title:literal),* }) => {
+        $(pub mod $day;)*
+        $(days!(@impl $year, $num, [$day], $title);)*
+    };
+
+    (@year $year:literal [$module:ident] { $($num:literal: $day:ident => $title:literal),* }) => {
+        pub mod $module {
+            $(pub mod $day;)*
+        }
+        $(days!(@impl $year, $num, [$module::$day], $title);)*
+    };
+
+    (@impl $year:literal, $num:literal, [$($day:ident)::+], $title:literal) => {
+        impl Solution for Day<$year, $num> {
+            fn year(&self) -> usize {
+                $year
+            }
+
+            fn day(&self) -> usize {
+                $num
+            }
+
+            fn title(&self) -> &'static str {
+                $title
+            }
+
+            fn examples(&self) -> &'static [&'static str] {
+                $($day::)+EXAMPLES
+            }
+
+            fn options(&self) -> &'static [Opt] {
+                $($day::)+OPTIONS
+            }
+
+            fn generate(&self, seed: u64, size: usize) -> String {
+                $($day::)+generate(&mut crate::util::rng::Rng::new(seed), size)
+            }
+
+            fn part1(&self, input: &str, opts: &Options) -> DynResult<Answer> {
+                Ok($($day::)+q1(input, opts)?.into())
+            }
+
+            fn part2(&self, input: &str, opts: &Options) -> DynResult<Answer> {
+                Ok($($day::)+q2(input, opts)?.into())
+            }
+        }
+    };
+}
+
+days! {
+    2020 => {
+        1: day1 => "Report Repair",
+        2: day2 => "Password Philosophy",
+        3: day3 => "Toboggan Trajectory",
+        4: day4 => "Passport Processing",
+        5: day5 => "Binary Boarding",
+        6: day6 => "Custom Customs",
+        7: day7 => "Handy Haversacks",
+        8: day8 => "Handheld Halting",
+        9: day9 => "Encoding Error",
+        10: day10 => "Adapter Array",
+        11: day11 => "Seating System",
+        12: day12 => "Rain Risk",
+        13: day13 => "Shuttle Search",
+        14: day14 => "Docking Data",
+        15: day15 => "Rambunctious Recitation",
+        16: day16 => "Ticket Translation",
+        17: day17 => "Conway Cubes",
+        18: day18 => "Operation Order",
+        19: day19 => "Monster Messages",
+        // 20: day20 => "Jurassic Jigsaw",
+        21: day21 => "Allergen Assessment",
+        22: day22 => "Crab Combat",
+        23: day23 => "Crab Cups",
+    },
+}
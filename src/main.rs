@@ -1,81 +1,369 @@
-#![allow(clippy::unreadable_literal, clippy::type_complexity)]
-#![feature(
-    min_const_generics,
-    array_value_iter,
-    array_windows,
-    const_in_array_repeat_expressions
-)]
-
-#[macro_use]
-pub mod prelude;
-pub mod util;
-
-/// Catch-all error type (works with anything that implements std::error::Error)
-pub type DynResult<T> = Result<T, Box<dyn std::error::Error>>;
-
-// Utility macro to make adding new days a breeze
-macro_rules! days {
-    ($($day:ident),* $(,)*) => {
-        $(mod $day;)*
-
-        fn route_day(day: &str, question: &str, input: &str, other_args: &[&str]) -> DynResult<()> {
-            let day = format!("day{}", day);
-
-            match day.as_str() {
-                $(stringify!($day) => match question {
-                    "1" => println!("Answer: {:?}", $day::q1(input, other_args)?),
-                    "2" => println!("Answer: {:?}", $day::q2(input, other_args)?),
-                    _ => return Err("Unknown question".into()),
-                })*
-                _ => return Err("Unknown day".into()),
-            }
+//! The `aoc20` command line interface.
 
-            Ok(())
-        }
+use aoc20::runner::Format;
+use aoc20::solution::{self, Solution, DEFAULT_YEAR};
+use aoc20::SOLUTIONS;
+use aoc20::{
+    answers, asm, bench, debugger, fetch, input, isa, json, options, runner, scaffold, trace,
+    watch, DynResult, Options,
+};
+
+/// Remove `--<name> <value>` from the list of args, returning the value.
+fn take_flag<'a>(args: &mut Vec<&'a str>, name: &str) -> DynResult<Option<&'a str>> {
+    let pos = match args.iter().position(|a| a.strip_prefix("--") == Some(name)) {
+        Some(pos) => pos,
+        None => return Ok(None),
     };
+
+    if pos + 1 >= args.len() {
+        return Err(format!("--{} requires a value", name).into());
+    }
+
+    let val = args.remove(pos + 1);
+    args.remove(pos);
+    Ok(Some(val))
 }
 
-days! {
-    day1,
-    day2,
-    day3,
-    day4,
-    day5,
-    day6,
-    day7,
-    day8,
-    day9,
-    day10,
-    day11,
-    day12,
-    day13,
-    day14,
-    day15,
-    day16,
-    day17,
-    day18,
-    day19,
-    // day20,
-    day21,
-    day22,
-    day23,
+/// Select a year's solutions from an (optional) range of days.
+fn select_days(year: usize, range: Option<&str>) -> DynResult<Vec<&'static dyn Solution>> {
+    let range = match range {
+        Some(range) => runner::parse_range(range)?,
+        None => 1..=25,
+    };
+
+    Ok(solution::for_year(year)
+        .filter(|s| range.contains(&s.day()))
+        .collect())
 }
 
-fn main() -> DynResult<()> {
-    let args = std::env::args().collect::<Vec<String>>();
-    let args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+/// Look up a solution from the given year.
+fn find_day(year: usize, day: Option<&&str>, usage: &str) -> DynResult<&'static dyn Solution> {
+    day.and_then(|d| d.parse::<usize>().ok())
+        .and_then(|d| solution::find(year, d))
+        .ok_or_else(|| format!("Must specify a valid {} day (e.g: {})", year, usage).into())
+}
 
-    let (day, question) = match (args.get(1), args.get(2)) {
+/// Look up a solution, and validate the question number.
+fn find_day_part(
+    year: usize,
+    day: Option<&&str>,
+    question: Option<&&str>,
+) -> DynResult<(&'static dyn Solution, usize)> {
+    let (day, question) = match (day, question) {
         (None, _) | (_, None) => return Err("Must specify day and question (e.g: 3 1)".into()),
         (Some(d), Some(q)) => (d, q),
     };
 
-    let input_path = format!("./inputs/{}.txt", day);
-    let input_path = std::path::Path::new(&input_path);
+    let sol = day
+        .parse::<usize>()
+        .ok()
+        .and_then(|d| solution::find(year, d))
+        .ok_or_else(|| format!("Unknown day (for {})", year))?;
+    let question = match question.parse::<usize>() {
+        Ok(q @ 1..=2) => q,
+        _ => return Err("Unknown question".into()),
+    };
+
+    Ok((sol, question))
+}
+
+fn main() -> DynResult<()> {
+    let args = std::env::args().collect::<Vec<String>>();
+    let mut args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    let mut fetcher = fetch::Fetcher::from_env();
+    if let Some(url) = take_flag(&mut args, "base-url")? {
+        fetcher.base_url = url.into();
+    }
+    if let Some(path) = take_flag(&mut args, "session")? {
+        fetcher.session_file = path.into();
+    }
+    let jobs = match take_flag(&mut args, "jobs")? {
+        Some(jobs) => jobs.parse::<usize>()?,
+        None => runner::DEFAULT_JOBS,
+    };
+    let format = match take_flag(&mut args, "format")? {
+        Some(format) => format.parse::<Format>()?,
+        None => Format::Text,
+    };
+    let year = match take_flag(&mut args, "year")?.map(String::from) {
+        Some(year) => year,
+        None => std::env::var("AOC_YEAR").unwrap_or_else(|_| DEFAULT_YEAR.to_string()),
+    };
+    let year = year
+        .parse::<usize>()
+        .map_err(|_| format!("invalid year: {}", year))?;
+
+    match args.get(1) {
+        Some(&"list") => {
+            for sol in SOLUTIONS {
+                println!("{} {:>2}: {}", sol.year(), sol.day(), sol.title());
+            }
+            return Ok(());
+        }
+        Some(&"help") => {
+            let sol = find_day(year, args.get(2), "help 9")?;
+            println!("{} Day {}: {}", sol.year(), sol.day(), sol.title());
+            println!();
+            println!("Options:");
+            options::print_help(sol.options());
+            return Ok(());
+        }
+        Some(&"gen") => {
+            let seed = match take_flag(&mut args, "seed")? {
+                Some(seed) => seed.parse::<u64>()?,
+                None => 0,
+            };
+            let size = match take_flag(&mut args, "size")? {
+                Some(size) => size.parse::<usize>()?,
+                None => 100,
+            };
+            let sol = find_day(year, args.get(2), "gen 8 --seed 1 --size 500")?;
+            println!("{}", sol.generate(seed, size));
+            return Ok(());
+        }
+        Some(&"new") => {
+            let title = take_flag(&mut args, "title")?.map(String::from);
+            let description = match take_flag(&mut args, "examples")? {
+                Some(path) => Some(
+                    std::fs::read_to_string(path)
+                        .map_err(|e| format!("Could not open {}: {}", path, e))?,
+                ),
+                None => None,
+            };
+            let day = match args.get(2).map(|d| d.parse::<usize>()) {
+                Some(Ok(day @ 1..=25)) => day,
+                _ => return Err("Must specify a day between 1 and 25 (e.g: new 24)".into()),
+            };
+
+            let new_day = scaffold::NewDay {
+                year,
+                day,
+                title,
+                description,
+            };
+            new_day.create(std::path::Path::new("."))?;
+            return Ok(());
+        }
+        Some(&"all") => {
+            runner::print_runs(
+                &runner::run_all(select_days(year, None)?, &fetcher, jobs),
+                format,
+            );
+            return Ok(());
+        }
+        Some(&"range") => {
+            let range = args
+                .get(2)
+                .ok_or("Must specify range of days (e.g: 1..=12)")?;
+            runner::print_runs(
+                &runner::run_all(select_days(year, Some(range))?, &fetcher, jobs),
+                format,
+            );
+            return Ok(());
+        }
+        Some(&"verify") | Some(&"record") => {
+            let path = take_flag(&mut args, "answers")?.unwrap_or(answers::DEFAULT_PATH);
+            let path = std::path::Path::new(path);
+            // `record` can start a new answers file, but there's nothing to
+            // verify against without one
+            if args[1] == "verify" && !path.exists() {
+                return Err(format!("No answers file at {}", path.to_string_lossy()).into());
+            }
+            let mut answers = answers::Answers::load(path)?;
+
+            let runs = runner::run_all(select_days(year, args.get(2).copied())?, &fetcher, jobs);
+
+            if args[1] == "verify" {
+                if !answers::verify(&runs, &answers) {
+                    eprintln!("Answers do not match {}", path.to_string_lossy());
+                    std::process::exit(1);
+                }
+            } else {
+                answers::record(&runs, &mut answers);
+                answers.save(path)?;
+            }
+            return Ok(());
+        }
+        Some(&"watch") => {
+            let alt_input = take_flag(&mut args, "input")?.map(std::path::PathBuf::from);
+            let interval = match take_flag(&mut args, "interval")? {
+                Some(ms) => std::time::Duration::from_millis(ms.parse()?),
+                None => watch::DEFAULT_INTERVAL,
+            };
+
+            let (sol, question) = find_day_part(year, args.get(2), args.get(3))?;
+            let opts = Options::parse(sol.options(), &args[4..])?;
+
+            watch::watch(sol, question, alt_input, &fetcher, &opts, interval)?;
+            return Ok(());
+        }
+        Some(&"debug") => {
+            let source = input::Source::from_flags(
+                take_flag(&mut args, "input")?,
+                take_flag(&mut args, "example")?,
+            )?;
+
+            let sol = find_day(year, args.get(2), "debug 8")?;
+            if (sol.year(), sol.day()) != (2020, 8) {
+                return Err("Only 2020 day 8 has a debugger".into());
+            }
+            let input = source.load(sol, &fetcher)?;
+
+            let vm = aoc20::day8::Vm::with_isa(&input, &isa::Isa::extended())?;
+            let mut dbg = debugger::Debugger::new(vm);
+            let stdin = std::io::stdin();
+            debugger::repl(&mut dbg, stdin.lock(), &mut std::io::stdout())?;
+            return Ok(());
+        }
+        Some(&"trace") => {
+            let replay = take_flag(&mut args, "replay")?;
+            let save = take_flag(&mut args, "save")?;
+            let source = input::Source::from_flags(
+                take_flag(&mut args, "input")?,
+                take_flag(&mut args, "example")?,
+            )?;
+
+            let sol = find_day(year, args.get(2), "trace 8")?;
+            if (sol.year(), sol.day()) != (2020, 8) {
+                return Err("Only 2020 day 8 can be traced".into());
+            }
+
+            let entries = match (replay, save) {
+                (Some(_), Some(_)) => return Err("cannot use both --replay and --save".into()),
+                (Some(path), None) => {
+                    let text = std::fs::read_to_string(path)
+                        .map_err(|e| format!("Could not open {}: {}", path, e))?;
+                    trace::parse(&text)?
+                }
+                (None, save) => {
+                    let input = source.load(sol, &fetcher)?;
+                    let program_len = input.lines().count();
+                    let tracer = match save {
+                        Some(path) => trace::Trace::file(std::path::Path::new(path))?,
+                        // the loop can't be longer than the program itself
+                        None => trace::Trace::ring(program_len + 1),
+                    };
+
+                    let (terminated, tracer) = trace::record(&input, tracer)?;
+                    if terminated {
+                        println!("program terminated");
+                        return Ok(());
+                    }
+                    match save {
+                        Some(path) => trace::parse(&std::fs::read_to_string(path)?)?,
+                        None => tracer.entries().into_iter().flatten().copied().collect(),
+                    }
+                }
+            };
+
+            match trace::find_cycle(&entries) {
+                Some(cycle) => print!("{}", cycle),
+                None => println!("no loop found in {} trace entries", entries.len()),
+            }
+            return Ok(());
+        }
+        Some(&"asm") => {
+            let encode = take_flag(&mut args, "encode")?;
+            let path = args
+                .get(2)
+                .ok_or("Must specify a program (e.g: asm prog.s)")?;
+            let program = asm::load(std::path::Path::new(path), &isa::Isa::extended())?;
+            match encode {
+                Some(out) => std::fs::write(out, asm::encode(&program))?,
+                None => {
+                    for instr in program {
+                        println!("{}", instr);
+                    }
+                }
+            }
+            return Ok(());
+        }
+        Some(&"disasm") => {
+            let path = args
+                .get(2)
+                .ok_or("Must specify a program (e.g: disasm prog.bin)")?;
+            let program = asm::load(std::path::Path::new(path), &isa::Isa::extended())?;
+            print!("{}", asm::disassemble(&program));
+            return Ok(());
+        }
+        Some(&"bench") => {
+            let parse_flag = |val: Option<&str>, default| -> DynResult<usize> {
+                Ok(val
+                    .map(|n| n.parse::<usize>())
+                    .transpose()?
+                    .unwrap_or(default))
+            };
+            let runs = parse_flag(take_flag(&mut args, "runs")?, 100)?;
+            let warmup = parse_flag(take_flag(&mut args, "warmup")?, 3)?;
+            let threshold = parse_flag(take_flag(&mut args, "threshold")?, 10)?;
+            let save_baseline = take_flag(&mut args, "save-baseline")?;
+            let baseline = take_flag(&mut args, "baseline")?;
+
+            let source = input::Source::from_flags(
+                take_flag(&mut args, "input")?,
+                take_flag(&mut args, "example")?,
+            )?;
+
+            let (sol, question) = find_day_part(year, args.get(2), args.get(3))?;
+            let input = source.load(sol, &fetcher)?;
+
+            let opts = Options::parse(sol.options(), &args[4..])?;
+
+            let stats = bench::bench(sol, question, &input, &opts, warmup, runs)?;
+            stats.print(input.len());
+
+            if let Some(path) = save_baseline {
+                stats.save_baseline(std::path::Path::new(path))?;
+            }
+
+            if let Some(path) = baseline {
+                let baseline = bench::Stats::load_baseline(std::path::Path::new(path))?;
+                let change = stats.change_from(&baseline);
+                println!("change from baseline: {:+.2}%", change * 100.0);
+                if change * 100.0 > threshold as f64 {
+                    eprintln!("Regressed by more than {}%", threshold);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
 
-    let mut input = std::fs::read_to_string(input_path)
-        .map_err(|e| format!("Could not open {}: {}", input_path.to_string_lossy(), e))?;
-    input.truncate(input.trim_end().len());
+    let source = input::Source::from_flags(
+        take_flag(&mut args, "input")?,
+        take_flag(&mut args, "example")?,
+    )?;
+
+    let (sol, question) = find_day_part(year, args.get(1), args.get(2))?;
+    let input = source.load(sol, &fetcher)?;
+
+    let opts = Options::parse(sol.options(), &args[3..])
+        .map_err(|e| format!("{} (see `help {}`)", e, sol.day()))?;
+
+    let run = runner::run(sol, question, &input, &opts);
+    if format == Format::Json {
+        json::print_lines(std::slice::from_ref(&run));
+    }
+
+    match run.answer {
+        Ok(answer) => {
+            if format == Format::Text {
+                println!("Answer: {}", answer);
+                if let Some(alloc) = run.alloc {
+                    println!("Memory: {}", alloc);
+                }
+            }
+        }
+        Err(e) => {
+            // printed using `Display`, so input errors are rendered with a caret
+            if format == Format::Text {
+                eprintln!("Error: {}", e);
+            }
+            std::process::exit(1);
+        }
+    }
 
-    route_day(day, question, &input, &args[3..])
+    Ok(())
 }
@@ -0,0 +1,187 @@
+//! Named, typed options which solutions can declare (e.g: day 9's
+//! `--preamble`).
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::DynResult;
+
+/// The type of value an option accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptKind {
+    Usize,
+    Isize,
+    Str,
+}
+
+impl OptKind {
+    fn validate(self, val: &str) -> Result<(), String> {
+        let ok = match self {
+            OptKind::Usize => val.parse::<usize>().is_ok(),
+            OptKind::Isize => val.parse::<isize>().is_ok(),
+            OptKind::Str => true,
+        };
+
+        if ok {
+            Ok(())
+        } else {
+            Err(format!("expected {}, got {:?}", self, val))
+        }
+    }
+}
+
+impl core::fmt::Display for OptKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            OptKind::Usize => write!(f, "unsigned integer"),
+            OptKind::Isize => write!(f, "integer"),
+            OptKind::Str => write!(f, "string"),
+        }
+    }
+}
+
+/// An option a solution accepts, passed on the command line as
+/// `--<name> <value>`.
+#[derive(Debug)]
+pub struct Opt {
+    pub name: &'static str,
+    pub kind: OptKind,
+    pub default: &'static str,
+    pub help: &'static str,
+}
+
+/// Option values passed to a solution.
+///
+/// Options which weren't explicitly set fall back to their declared default.
+///
+/// ```
+/// use aoc20::options::{Opt, OptKind, Options};
+///
+/// const PREAMBLE: Opt = Opt {
+///     name: "preamble",
+///     kind: OptKind::Usize,
+///     default: "25",
+///     help: "preamble length",
+/// };
+///
+/// let opts = Options::parse(&[PREAMBLE], &["--preamble", "5"]).unwrap();
+/// assert_eq!(opts.get::<usize>(&PREAMBLE).unwrap(), 5);
+/// assert_eq!(Options::default().get::<usize>(&PREAMBLE).unwrap(), 25);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    vals: HashMap<String, String>,
+}
+
+impl Options {
+    /// Parse a list of `--<name> <value>` pairs, validating them against a
+    /// solution's declared options.
+    pub fn parse(specs: &[Opt], args: &[&str]) -> DynResult<Options> {
+        let mut opts = Options::default();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unexpected argument: {}", arg))?;
+            let spec = specs
+                .iter()
+                .find(|s| s.name == name)
+                .ok_or_else(|| format!("unknown option: --{}", name))?;
+            let val = args
+                .next()
+                .ok_or_else(|| format!("--{} requires a value", name))?;
+
+            spec.kind
+                .validate(val)
+                .map_err(|e| format!("invalid value for --{}: {}", name, e))?;
+
+            if opts
+                .vals
+                .insert(name.to_string(), val.to_string())
+                .is_some()
+            {
+                return Err(format!("--{} specified more than once", name).into());
+            }
+        }
+
+        Ok(opts)
+    }
+
+    /// Explicitly set an option's value.
+    pub fn with(mut self, name: &str, val: &str) -> Options {
+        self.vals.insert(name.to_string(), val.to_string());
+        self
+    }
+
+    /// Get an option's value, falling back to its default if it wasn't set.
+    pub fn get<T>(&self, opt: &Opt) -> DynResult<T>
+    where
+        T: FromStr,
+        T::Err: core::fmt::Display,
+    {
+        let val = self.vals.get(opt.name).map(String::as_str);
+        val.unwrap_or(opt.default)
+            .parse::<T>()
+            .map_err(|e| format!("invalid value for --{}: {}", opt.name, e).into())
+    }
+}
+
+/// Print a solution's options, along with their types and defaults.
+pub fn print_help(specs: &[Opt]) {
+    if specs.is_empty() {
+        println!("(no options)");
+        return;
+    }
+
+    let name_w = specs.iter().map(|s| s.name.len()).max().unwrap_or(0);
+    for spec in specs {
+        println!(
+            "  --{:<name_w$} <{}>  {} (default: {})",
+            spec.name,
+            spec.kind,
+            spec.help,
+            spec.default,
+            name_w = name_w
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPECS: &[Opt] = &[
+        Opt {
+            name: "preamble",
+            kind: OptKind::Usize,
+            default: "25",
+            help: "preamble length",
+        },
+        Opt {
+            name: "name",
+            kind: OptKind::Str,
+            default: "",
+            help: "a name",
+        },
+    ];
+
+    #[test]
+    fn parse() {
+        let opts = Options::parse(SPECS, &["--preamble", "5"]).unwrap();
+        assert_eq!(opts.get::<usize>(&SPECS[0]).unwrap(), 5);
+        assert_eq!(opts.get::<String>(&SPECS[1]).unwrap(), "");
+
+        let opts = Options::parse(SPECS, &[]).unwrap();
+        assert_eq!(opts.get::<usize>(&SPECS[0]).unwrap(), 25);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Options::parse(SPECS, &["--preamble", "-5"]).is_err());
+        assert!(Options::parse(SPECS, &["--preamble"]).is_err());
+        assert!(Options::parse(SPECS, &["--moves", "5"]).is_err());
+        assert!(Options::parse(SPECS, &["5"]).is_err());
+        assert!(Options::parse(SPECS, &["--name", "a", "--name", "b"]).is_err());
+    }
+}
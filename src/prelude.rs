@@ -10,16 +10,23 @@ pub use std::io::prelude::*;
 pub use iter_to_array::*; // collect into fixed size arrays
 pub use itertools::{Itertools, MinMaxResult};
 
+pub use crate::error::{InputError, OptionExt, ResultExt};
+pub use crate::options::{Opt, OptKind, Options};
 pub use crate::DynResult;
 
 pub use crate::util::ext_traits::*;
 pub use crate::util::gcd_lcm::GcdLcm;
+pub use crate::util::grid::Grid;
+pub use crate::util::point::{Point, Vector};
+pub use crate::util::rng::Rng;
 
 /// misc useful AoC things
 pub mod aoc {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
+    pub use crate::runner::mark_parsed;
+
     /// Opaque type representing the hash of a value
     #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
     pub struct HashVal(u64);
@@ -0,0 +1,429 @@
+//! Running solutions, and measuring how long they take.
+
+use std::any::Any;
+use std::cell::Cell;
+use std::ops::RangeInclusive;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::alloc::{self, AllocStats};
+use crate::fetch::Fetcher;
+use crate::json;
+use crate::options::Options;
+use crate::solution::{Answer, Solution};
+use crate::DynResult;
+
+thread_local! {
+    static PARSED_AT: Cell<Option<Instant>> = Cell::new(None);
+}
+
+/// Record that the currently running solution has finished parsing its input.
+///
+/// Called at the end of each day's `munge_input!` macro, and used to split the
+/// time spent parsing from the time spent solving. Solutions which never call
+/// it have their entire runtime attributed to solving.
+pub fn mark_parsed() {
+    PARSED_AT.with(|t| t.set(Some(Instant::now())));
+}
+
+/// The outcome of running a single part of a solution.
+pub struct Run {
+    pub year: usize,
+    pub day: usize,
+    pub part: usize,
+    pub title: &'static str,
+    pub answer: DynResult<Answer>,
+    pub parse: Duration,
+    pub solve: Duration,
+    /// Only recorded when the `alloc-stats` feature is enabled.
+    pub alloc: Option<AllocStats>,
+}
+
+impl Run {
+    pub fn total(&self) -> Duration {
+        self.parse + self.solve
+    }
+}
+
+/// Run a single part of a solution, timing the parse and solve phases.
+pub fn run(sol: &dyn Solution, part: usize, input: &str, opts: &Options) -> Run {
+    PARSED_AT.with(|t| t.set(None));
+
+    let start = Instant::now();
+    let (answer, alloc) = alloc::measure(|| sol.part(part, input, opts));
+    let end = Instant::now();
+
+    let parsed_at = PARSED_AT.with(|t| t.take()).unwrap_or(start);
+
+    Run {
+        year: sol.year(),
+        day: sol.day(),
+        part,
+        title: sol.title(),
+        answer,
+        parse: parsed_at - start,
+        solve: end - parsed_at,
+        alloc,
+    }
+}
+
+/// Default number of worker threads used by [`run_all`].
+///
+/// Runs are sequential by default, since solutions running concurrently skew
+/// each other's timings (and allocation stats).
+pub const DEFAULT_JOBS: usize = 1;
+
+/// A single part of a solution, queued to run on a worker thread.
+struct Task {
+    sol: &'static dyn Solution,
+    part: usize,
+    input: Arc<DynResult<String>>,
+}
+
+impl Task {
+    /// Run the task, turning input errors and panics into failed runs.
+    fn run(&self) -> Run {
+        let input = match &*self.input {
+            Ok(input) => input,
+            Err(e) => return self.failed(e.to_string()),
+        };
+
+        let (sol, part) = (self.sol, self.part);
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            run(sol, part, input, &Options::default())
+        }));
+
+        match res {
+            Ok(run) => run,
+            Err(payload) => self.failed(format!("panicked: {}", panic_message(&*payload))),
+        }
+    }
+
+    fn failed(&self, msg: String) -> Run {
+        Run {
+            year: self.sol.year(),
+            day: self.sol.day(),
+            part: self.part,
+            title: self.sol.title(),
+            answer: Err(msg.into()),
+            parse: Duration::default(),
+            solve: Duration::default(),
+            alloc: None,
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(s) => s,
+        None => payload
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .unwrap_or("unknown panic"),
+    }
+}
+
+/// Run both parts of each solution against its input, using `jobs` worker
+/// threads.
+///
+/// Runs are returned in the same order as the solutions were provided, and
+/// solutions which panic are reported as failed runs instead of aborting the
+/// rest. Allocation stats are only reported when running sequentially.
+pub fn run_all(
+    sols: impl IntoIterator<Item = &'static dyn Solution>,
+    fetcher: &Fetcher,
+    jobs: usize,
+) -> Vec<Run> {
+    let mut tasks = Vec::new();
+    for sol in sols {
+        // inputs are loaded up-front, since fetching missing inputs is
+        // rate-limited anyways
+        let input = Arc::new(fetcher.load(sol.year(), sol.day()));
+        for part in 1..=2 {
+            tasks.push(Task {
+                sol,
+                part,
+                input: input.clone(),
+            });
+        }
+    }
+    run_tasks(tasks, jobs)
+}
+
+fn run_tasks(tasks: Vec<Task>, jobs: usize) -> Vec<Run> {
+    let workers = jobs.max(1).min(tasks.len());
+    // the allocation counters are process-wide, so with several workers they'd
+    // include whatever the other runs were allocating at the same time
+    let parallel = workers > 1;
+    let queue = Arc::new(Mutex::new(tasks.into_iter().enumerate()));
+    let (tx, rx) = mpsc::channel();
+
+    let handles = (0..workers)
+        .map(|_| {
+            let queue = queue.clone();
+            let tx = tx.clone();
+            thread::spawn(move || loop {
+                // panics are caught by `Task::run`, so the lock is never poisoned
+                let next = queue.lock().unwrap().next();
+                match next {
+                    Some((i, task)) => {
+                        let mut run = task.run();
+                        if parallel {
+                            run.alloc = None;
+                        }
+                        tx.send((i, run)).unwrap()
+                    }
+                    None => break,
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+
+    let mut runs = rx.iter().collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    runs.sort_by_key(|(i, _)| *i);
+    runs.into_iter().map(|(_, run)| run).collect()
+}
+
+/// Parse a range of days, using Rust's range syntax (e.g: `1..=12`, `3..7`).
+/// A single day (e.g: `5`) is also accepted.
+///
+/// ```
+/// use aoc20::runner::parse_range;
+///
+/// assert_eq!(parse_range("3..7").unwrap(), 3..=6);
+/// assert_eq!(parse_range("5").unwrap(), 5..=5);
+/// assert!(parse_range("7..3").is_err());
+/// ```
+pub fn parse_range(s: &str) -> DynResult<RangeInclusive<usize>> {
+    let range = if let Some((start, end)) = s.split_once("..=") {
+        start.parse()?..=end.parse()?
+    } else if let Some((start, end)) = s.split_once("..") {
+        let end = end.parse::<usize>()?;
+        start.parse()?..=end.checked_sub(1).ok_or("range is empty")?
+    } else {
+        let day = s.parse()?;
+        day..=day
+    };
+
+    if range.is_empty() {
+        return Err("range is empty".into());
+    }
+
+    Ok(range)
+}
+
+/// Format a duration using the most appropriate unit.
+pub fn fmt_duration(d: Duration) -> String {
+    let nanos = d.as_nanos();
+    if nanos < 1_000 {
+        format!("{}ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.2}µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2}ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2}s", nanos as f64 / 1e9)
+    }
+}
+
+/// How runs are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Human readable output (e.g: an aligned table).
+    Text,
+    /// One JSON object per run (see [`crate::json`]).
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format: {} (expected text or json)", s)),
+        }
+    }
+}
+
+/// Print runs in the given format.
+pub fn print_runs(runs: &[Run], format: Format) {
+    match format {
+        Format::Text => print_table(runs),
+        Format::Json => json::print_lines(runs),
+    }
+}
+
+/// Print an aligned table of answers and timings, followed by the total time.
+pub fn print_table(runs: &[Run]) {
+    let answers = runs
+        .iter()
+        .map(|run| match &run.answer {
+            Ok(ans) => ans.to_string(),
+            // input errors are multi-line diagnostics, so only show the summary
+            Err(e) => format!("error: {}", e.to_string().lines().next().unwrap_or("")),
+        })
+        .collect::<Vec<_>>();
+
+    let title_w = runs.iter().map(|r| r.title.len()).max().unwrap_or(0).max(5);
+    let answer_w = answers.iter().map(|a| a.len()).max().unwrap_or(0).max(6);
+
+    // allocation columns are only shown with the `alloc-stats` feature enabled
+    let show_alloc = runs.iter().any(|r| r.alloc.is_some());
+    let alloc_cols = |allocs: &str, bytes: &str, peak: &str| {
+        if show_alloc {
+            format!("  {:>10}  {:>10}  {:>10}", allocs, bytes, peak)
+        } else {
+            String::new()
+        }
+    };
+
+    println!(
+        "{:>3}  {:>4}  {:<title_w$}  {:<answer_w$}  {:>10}  {:>10}{}",
+        "Day",
+        "Part",
+        "Title",
+        "Answer",
+        "Parse",
+        "Solve",
+        alloc_cols("Allocs", "Allocated", "Peak"),
+        title_w = title_w,
+        answer_w = answer_w,
+    );
+
+    for (run, answer) in runs.iter().zip(answers.iter()) {
+        let alloc = match run.alloc {
+            Some(a) => alloc_cols(
+                &a.allocs.to_string(),
+                &alloc::fmt_bytes(a.bytes),
+                &alloc::fmt_bytes(a.peak),
+            ),
+            None => alloc_cols("-", "-", "-"),
+        };
+        println!(
+            "{:>3}  {:>4}  {:<title_w$}  {:<answer_w$}  {:>10}  {:>10}{}",
+            run.day,
+            run.part,
+            run.title,
+            answer,
+            fmt_duration(run.parse),
+            fmt_duration(run.solve),
+            alloc,
+            title_w = title_w,
+            answer_w = answer_w,
+        );
+    }
+
+    let total = runs.iter().map(Run::total).sum::<Duration>();
+    println!("Total: {}", fmt_duration(total));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_range("1..=12").unwrap(), 1..=12);
+        assert_eq!(parse_range("3..7").unwrap(), 3..=6);
+        assert_eq!(parse_range("5").unwrap(), 5..=5);
+        assert!(parse_range("5..5").is_err());
+        assert!(parse_range("7..=3").is_err());
+        assert!(parse_range("x..=3").is_err());
+    }
+
+    struct Panicky;
+
+    impl Solution for Panicky {
+        fn year(&self) -> usize {
+            2020
+        }
+
+        fn day(&self) -> usize {
+            10
+        }
+
+        fn title(&self) -> &'static str {
+            "Panicky"
+        }
+
+        fn examples(&self) -> &'static [&'static str] {
+            &[]
+        }
+
+        fn options(&self) -> &'static [crate::options::Opt] {
+            &[]
+        }
+
+        fn generate(&self, _seed: u64, _size: usize) -> String {
+            String::new()
+        }
+
+        fn part1(&self, _input: &str, _opts: &Options) -> DynResult<Answer> {
+            unimplemented!("general case")
+        }
+
+        fn part2(&self, input: &str, _opts: &Options) -> DynResult<Answer> {
+            Ok(input.len().into())
+        }
+    }
+
+    #[test]
+    fn parallel() {
+        let day1 = crate::solution::find(2020, 1).unwrap();
+        let day1_input = Arc::new(Ok(day1.examples()[0].trim().to_string()));
+
+        let mut tasks = Vec::new();
+        for &(sol, input) in &[
+            (day1, &day1_input),
+            (&Panicky as &dyn Solution, &Arc::new(Ok("abc".to_string()))),
+            (day1, &Arc::new(Err("missing input".into()))),
+        ] {
+            for part in 1..=2 {
+                tasks.push(Task {
+                    sol,
+                    part,
+                    input: input.clone(),
+                });
+            }
+        }
+
+        let runs = run_tasks(tasks, 4);
+        let answers = runs
+            .iter()
+            .map(|r| (r.day, r.part, r.answer.as_ref().map_err(|e| e.to_string())))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            answers,
+            [
+                (1, 1, Ok(&Answer::Int(514579))),
+                (1, 2, Ok(&Answer::Int(241861950))),
+                (
+                    10,
+                    1,
+                    Err("panicked: not implemented: general case".to_string())
+                ),
+                (10, 2, Ok(&Answer::Int(3))),
+                (1, 1, Err("missing input".to_string())),
+                (1, 2, Err("missing input".to_string())),
+            ]
+        );
+        assert!(runs.iter().all(|r| r.alloc.is_none()));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(fmt_duration(Duration::from_nanos(999)), "999ns");
+        assert_eq!(fmt_duration(Duration::from_micros(1500)), "1.50ms");
+        assert_eq!(fmt_duration(Duration::from_secs(2)), "2.00s");
+    }
+}
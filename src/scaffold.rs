@@ -0,0 +1,389 @@
+//! Scaffolding for new days, based on the `src/dayX.rs` template.
+
+use std::path::{Path, PathBuf};
+
+use crate::solution::DEFAULT_YEAR;
+use crate::DynResult;
+
+/// Template new days are instantiated from (relative to the crate root).
+pub const TEMPLATE_PATH: &str = "src/dayX.rs";
+/// File containing the `days!` macro invocation (relative to the crate root).
+pub const REGISTRY_PATH: &str = "src/lib.rs";
+
+/// Everything the template needs to be instantiated.
+pub struct NewDay {
+    pub year: usize,
+    pub day: usize,
+    pub title: Option<String>,
+    /// Contents of a saved copy of the question's description (i.e: the HTML
+    /// of `https://adventofcode.com/<year>/day/<day>`).
+    pub description: Option<String>,
+}
+
+/// Module a year's days live in (2020's days live at the crate root).
+pub fn year_module(year: usize) -> Option<String> {
+    if year == DEFAULT_YEAR {
+        None
+    } else {
+        Some(format!("y{}", year))
+    }
+}
+
+/// Path to a day's source file, relative to the crate root.
+pub fn day_path(year: usize, day: usize) -> PathBuf {
+    let mut path = PathBuf::from("src");
+    if let Some(module) = year_module(year) {
+        path.push(module);
+    }
+    path.join(format!("day{}.rs", day))
+}
+
+impl NewDay {
+    /// The question's title, either specified explicitly, or extracted from
+    /// the saved description.
+    fn title(&self) -> String {
+        self.title
+            .clone()
+            .or_else(|| self.description.as_deref().and_then(extract_title))
+            .unwrap_or_else(|| format!("Day {}", self.day))
+    }
+
+    /// Create `src/day<N>.rs` (or `src/y<year>/day<N>.rs`), register it in
+    /// the `days!` macro, and create an empty `inputs/<year>/<N>.txt`
+    /// placeholder.
+    pub fn create(&self, root: &Path) -> DynResult<()> {
+        let day_path = root.join(day_path(self.year, self.day));
+        if day_path.exists() {
+            return Err(format!("{} already exists", day_path.to_string_lossy()).into());
+        }
+
+        let template = std::fs::read_to_string(root.join(TEMPLATE_PATH))?;
+        let examples = match &self.description {
+            Some(description) => extract_examples(description),
+            None => Vec::new(),
+        };
+        let source = instantiate(&template, &examples)?;
+
+        let registry_path = root.join(REGISTRY_PATH);
+        let registry = std::fs::read_to_string(&registry_path)?;
+        let registry = register(&registry, self.year, self.day, &self.title())?;
+
+        if let Some(dir) = day_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&day_path, source)?;
+        std::fs::write(&registry_path, registry)?;
+        println!("created {}", day_path.to_string_lossy());
+        println!(
+            "registered {} day {} in {}",
+            self.year, self.day, REGISTRY_PATH
+        );
+        if !examples.is_empty() {
+            println!("imported {} example(s)", examples.len());
+        }
+
+        let input_dir = root.join("inputs").join(self.year.to_string());
+        let input_path = input_dir.join(format!("{}.txt", self.day));
+        if !input_path.exists() {
+            std::fs::create_dir_all(&input_dir)?;
+            std::fs::write(&input_path, "")?;
+            println!("created {}", input_path.to_string_lossy());
+        }
+
+        Ok(())
+    }
+}
+
+/// Extract the title from a question description's `--- Day N: Title ---`
+/// header.
+pub fn extract_title(description: &str) -> Option<String> {
+    let header = description.split("--- Day ").nth(1)?;
+    let header = header.split(" ---").next()?;
+    let title = header.split_once(": ")?.1.trim();
+    if title.is_empty() {
+        return None;
+    }
+    Some(unescape_html(title))
+}
+
+/// Extract the contents of every `<pre><code>` block in a question
+/// description.
+pub fn extract_examples(description: &str) -> Vec<String> {
+    description
+        .split("<pre><code>")
+        .skip(1)
+        .filter_map(|block| block.split("</code></pre>").next())
+        .map(|block| unescape_html(&strip_tags(block)).trim().to_string())
+        .filter(|block| !block.is_empty())
+        .collect()
+}
+
+fn strip_tags(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut in_tag = false;
+    for c in s.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn unescape_html(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Wrap `s` (on its own lines) in a raw string literal, with enough `#`s that
+/// nothing in `s` ends the literal early.
+fn raw_string(s: &str) -> String {
+    let longest = s
+        .split('"')
+        .skip(1)
+        .map(|after| after.len() - after.trim_start_matches('#').len())
+        .max()
+        .unwrap_or(0);
+    let hashes = "#".repeat(longest + 1);
+    format!("r{0}\"\n{1}\n\"{0}", hashes, s)
+}
+
+/// Instantiate the template, replacing its dummy example with the extracted
+/// examples (if there are any).
+pub fn instantiate(template: &str, examples: &[String]) -> DynResult<String> {
+    if examples.is_empty() {
+        return Ok(template.to_string());
+    }
+
+    const EXAMPLES_MARKER: &str = "/// Example inputs from the question description.";
+    let head = template
+        .split(EXAMPLES_MARKER)
+        .next()
+        .filter(|head| head.len() != template.len())
+        .ok_or("template is missing the examples section")?;
+
+    let mut out = head.to_string();
+
+    let names = (1..=examples.len())
+        .map(|i| format!("EXAMPLE_{}", i))
+        .collect::<Vec<_>>();
+    out += EXAMPLES_MARKER;
+    out += &format!("\npub const EXAMPLES: &[&str] = &[{}];\n", names.join(", "));
+
+    for (name, example) in names.iter().zip(examples) {
+        out += &format!("\nconst {}: &str = {};\n", name, raw_string(example));
+    }
+
+    out += "\n#[cfg(test)]\nmod tests {\n    use super::*;\n";
+    for name in &names {
+        let e = name.trim_start_matches("EXAMPLE_");
+        out += &format!(
+            r#"
+    #[test]
+    fn q1_e{e}() {{
+        let input = {name};
+        let expected = {{ 0 }};
+        let q = q1;
+
+        assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    }}
+
+    // #[test]
+    // fn q2_e{e}() {{
+    //     let input = {name};
+    //     let expected = {{ 0 }};
+    //     let q = q2;
+
+    //     assert_eq!(q(input.trim(), &Options::default()).unwrap(), expected);
+    // }}
+"#,
+            e = e,
+            name = name
+        );
+    }
+    out += "}\n";
+
+    Ok(out)
+}
+
+/// Add a day to the `days!` macro invocation, keeping it sorted. If the day
+/// is already listed, but commented out, it's uncommented instead. Years
+/// without any days yet get a new block.
+pub fn register(source: &str, year: usize, day: usize, title: &str) -> DynResult<String> {
+    let start = source
+        .find("\ndays! {\n")
+        .ok_or("could not find `days!` invocation")?
+        + 1;
+    let end = start
+        + source[start..]
+            .find("\n}\n")
+            .ok_or("unterminated `days!` invocation")?;
+
+    // e.g: `2019 in y2019 => {`
+    let block_year = |ln: &str| -> Option<usize> {
+        let ln = ln.trim();
+        if !ln.ends_with("=> {") {
+            return None;
+        }
+        ln.split(' ').next()?.parse().ok()
+    };
+
+    let entry_day = |ln: &str| -> Option<usize> {
+        let ln = ln.trim().trim_start_matches("//").trim();
+        let (n, rest) = ln.split_once(": ")?;
+        if !rest.starts_with("day") {
+            return None;
+        }
+        n.parse().ok()
+    };
+
+    let entry = format!("        {}: day{} => {:?},", day, day, title);
+    let mut lines = source[start..end]
+        .split('\n')
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    match lines.iter().position(|ln| block_year(ln) == Some(year)) {
+        None => {
+            let header = match year_module(year) {
+                Some(module) => format!("    {} in {} => {{", year, module),
+                None => format!("    {} => {{", year),
+            };
+            let i = lines
+                .iter()
+                .position(|ln| matches!(block_year(ln), Some(y) if y > year))
+                .unwrap_or(lines.len());
+            lines.splice(i..i, vec![header, entry, "    },".to_string()]);
+        }
+        Some(block_start) => {
+            let block_end = block_start
+                + lines[block_start..]
+                    .iter()
+                    .position(|ln| ln.trim() == "},")
+                    .ok_or_else(|| format!("unterminated block for {}", year))?;
+            let block = block_start + 1..block_end;
+
+            match lines[block.clone()]
+                .iter()
+                .position(|ln| entry_day(ln) == Some(day))
+            {
+                Some(i) if lines[block.start + i].trim().starts_with("//") => {
+                    lines[block.start + i] = entry
+                }
+                Some(_) => return Err(format!("{} day {} is already registered", year, day).into()),
+                None => {
+                    let i = lines[block.clone()]
+                        .iter()
+                        .position(|ln| matches!(entry_day(ln), Some(d) if d > day))
+                        .map_or(block.end, |i| block.start + i);
+                    lines.insert(i, entry);
+                }
+            }
+        }
+    }
+
+    Ok(format!(
+        "{}{}{}",
+        &source[..start],
+        lines.join("\n"),
+        &source[end..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTION: &str = r#"
+<article class="day-desc"><h2>--- Day 7: Handy Haversacks ---</h2>
+<p>For example:</p>
+<pre><code>light red bags contain 1 bright white bag, 2 muted yellow bags.
+<em>faded blue</em> bags contain no other bags.
+</code></pre>
+<p>Some inline <code>code</code>.</p>
+<pre><code>a -&gt; b &amp; c
+</code></pre>
+</article>
+"#;
+
+    #[test]
+    fn extract() {
+        assert_eq!(
+            extract_title(DESCRIPTION),
+            Some("Handy Haversacks".to_string())
+        );
+        assert_eq!(
+            extract_examples(DESCRIPTION),
+            [
+                "light red bags contain 1 bright white bag, 2 muted yellow bags.\nfaded blue bags contain no other bags.",
+                "a -> b & c"
+            ]
+        );
+    }
+
+    const REGISTRY: &str = r#"
+days! {
+    2020 => {
+        1: day1 => "Report Repair",
+        3: day3 => "Toboggan Trajectory",
+        // 20: day20 => "Jurassic Jigsaw",
+    },
+}
+"#;
+
+    #[test]
+    fn register_days() {
+        let out = register(REGISTRY, 2020, 2, "Password Philosophy").unwrap();
+        assert!(out.contains(
+            "        1: day1 => \"Report Repair\",\n        2: day2 => \"Password Philosophy\",\n        3: day3"
+        ));
+
+        let out = register(REGISTRY, 2020, 20, "Jurassic Jigsaw").unwrap();
+        assert!(out.contains("\n        20: day20 => \"Jurassic Jigsaw\",\n    },\n}"));
+
+        let out = register(REGISTRY, 2020, 25, "Day 25").unwrap();
+        assert!(out.contains("        // 20: day20 => \"Jurassic Jigsaw\",\n        25: day25"));
+
+        assert!(register(REGISTRY, 2020, 3, "Toboggan Trajectory").is_err());
+    }
+
+    #[test]
+    fn register_years() {
+        let out = register(REGISTRY, 2019, 1, "The Tyranny of the Rocket Equation").unwrap();
+        assert!(out.contains(
+            "days! {\n    2019 in y2019 => {\n        1: day1 => \"The Tyranny of the Rocket Equation\",\n    },\n    2020 => {"
+        ));
+
+        let out = register(&out, 2019, 2, "1202 Program Alarm").unwrap();
+        assert!(out.contains("\n        2: day2 => \"1202 Program Alarm\",\n    },\n    2020 => {"));
+
+        let out = register(REGISTRY, 2021, 1, "Sonar Sweep").unwrap();
+        assert!(out.contains(
+            "    },\n    2021 in y2021 => {\n        1: day1 => \"Sonar Sweep\",\n    },\n}"
+        ));
+
+        assert_eq!(day_path(2020, 3), Path::new("src/day3.rs"));
+        assert_eq!(day_path(2019, 3), Path::new("src/y2019/day3.rs"));
+    }
+
+    #[test]
+    fn instantiate_template() {
+        let template = include_str!("dayX.rs");
+        assert_eq!(instantiate(template, &[]).unwrap(), template);
+
+        let out = instantiate(template, &["1\n2".into(), "3".into()]).unwrap();
+        assert!(out.contains("pub const EXAMPLES: &[&str] = &[EXAMPLE_1, EXAMPLE_2];"));
+        assert!(out.contains("const EXAMPLE_2: &str = r#\"\n3\n\"#;"));
+        assert!(out.contains("fn q1_e2()"));
+        assert!(!out.contains("dummy"));
+
+        // examples which would end a `r#"` string early
+        let out = instantiate(template, &["say \"#hi\"##".into()]).unwrap();
+        assert!(out.contains("const EXAMPLE_1: &str = r###\"\nsay \"#hi\"##\n\"###;"));
+    }
+}
@@ -0,0 +1,170 @@
+//! The common interface implemented by every day's solution.
+//!
+//! Solutions are registered using the `days!` macro in `lib.rs`, which
+//! implements [`Solution`] for the corresponding [`Day`], and adds it to
+//! [`SOLUTIONS`](crate::SOLUTIONS). Solutions are keyed by (year, day), so a
+//! single binary can hold solutions for multiple events.
+
+use crate::error::InputError;
+use crate::options::{Opt, Options};
+use crate::DynResult;
+
+/// The answer to a question.
+///
+/// Most questions have a numeric answer, but some (e.g: day 21) expect a
+/// string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(i128),
+    Str(String),
+}
+
+impl core::fmt::Display for Answer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::Str(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+macro_rules! answer_from_int_impl {
+    ($($type:ty),*) => ($(
+        impl From<$type> for Answer {
+            fn from(n: $type) -> Answer {
+                Answer::Int(n as i128)
+            }
+        }
+    )*)
+}
+
+answer_from_int_impl! { u8, u16, u32, u64, usize, i8, i16, i32, i64, isize }
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        Answer::Str(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Answer {
+        Answer::Str(s.to_owned())
+    }
+}
+
+/// Year used when one isn't specified explicitly (e.g: via `--year`).
+pub const DEFAULT_YEAR: usize = 2020;
+
+/// A solution to a particular day's questions.
+pub trait Solution: Sync {
+    /// The year of the event the question is from.
+    fn year(&self) -> usize;
+
+    /// The day of the month the question was released on.
+    fn day(&self) -> usize;
+
+    /// The title of the question (e.g: "Report Repair").
+    fn title(&self) -> &'static str;
+
+    /// Example inputs from the question description.
+    fn examples(&self) -> &'static [&'static str];
+
+    /// Options accepted by the solution (e.g: `--preamble 5`).
+    fn options(&self) -> &'static [Opt];
+
+    /// Solve the first part of the question.
+    fn part1(&self, input: &str, opts: &Options) -> DynResult<Answer>;
+
+    /// Solve the second part of the question.
+    fn part2(&self, input: &str, opts: &Options) -> DynResult<Answer>;
+
+    /// Generate a synthetic input from a seed. What `size` means varies from
+    /// day to day (e.g: number of lines, grid width, etc...).
+    fn generate(&self, seed: u64, size: usize) -> String;
+
+    /// Solve a particular part of the question (i.e: `1` or `2`).
+    ///
+    /// Any [`InputError`]s are tagged with the solution's day.
+    fn part(&self, part: usize, input: &str, opts: &Options) -> DynResult<Answer> {
+        let res = match part {
+            1 => self.part1(input, opts),
+            2 => self.part2(input, opts),
+            _ => Err("Unknown question".into()),
+        };
+
+        res.map_err(|mut e| {
+            if let Some(e) = e.downcast_mut::<InputError>() {
+                e.day.get_or_insert(self.day());
+            }
+            e
+        })
+    }
+}
+
+/// Marker type which the `days!` macro implements [`Solution`] on.
+pub struct Day<const YEAR: usize, const N: usize>;
+
+/// Look up the solution for a particular day of a particular year.
+pub fn find(year: usize, day: usize) -> Option<&'static dyn Solution> {
+    crate::SOLUTIONS
+        .iter()
+        .copied()
+        .find(|s| (s.year(), s.day()) == (year, day))
+}
+
+/// Every solution from a particular year, ordered by day.
+pub fn for_year(year: usize) -> impl Iterator<Item = &'static dyn Solution> {
+    crate::SOLUTIONS
+        .iter()
+        .copied()
+        .filter(move |s| s.year() == year)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_sorted_and_unique() {
+        let days = crate::SOLUTIONS
+            .iter()
+            .map(|s| (s.year(), s.day()))
+            .collect::<Vec<_>>();
+        assert!(days.windows(2).all(|w| w[0] < w[1]));
+
+        assert_eq!(find(2020, 8).unwrap().title(), "Handheld Halting");
+        assert!(find(2019, 8).is_none());
+        assert_eq!(for_year(2020).count(), crate::SOLUTIONS.len());
+    }
+
+    #[test]
+    fn generated_inputs() {
+        for sol in crate::SOLUTIONS {
+            for seed in 0..3 {
+                let input = sol.generate(seed, 10);
+                assert_eq!(input, sol.generate(seed, 10));
+                for part in 1..=2 {
+                    // 30 million turns is _way_ too slow for a debug build
+                    if (sol.day(), part) == (15, 2) {
+                        continue;
+                    }
+                    let res = sol.part(part, &input, &Options::default());
+                    assert!(
+                        res.is_ok(),
+                        "day {} part {} (seed {})",
+                        sol.day(),
+                        part,
+                        seed
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn answer_display() {
+        assert_eq!(Answer::from(514579usize).to_string(), "514579");
+        assert_eq!(Answer::from(-3i64).to_string(), "-3");
+        assert_eq!(Answer::from("a,b,c").to_string(), "a,b,c");
+    }
+}
@@ -0,0 +1,298 @@
+//! Recording (and replaying) the instructions executed by day 8's [`Vm`].
+//!
+//! Traces are opt-in (see [`Vm::with_trace`]), and are either kept in memory
+//! or written out as text, one [`TraceEntry`] per line. `cargo run -- trace 8`
+//! uses them to print exactly which instructions make up a program's loop.
+//!
+//! [`Vm`]: crate::day8::Vm
+//! [`Vm::with_trace`]: crate::day8::Vm::with_trace
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::day8::{Instr, Vm};
+use crate::error::{InputError, ResultExt};
+use crate::isa::Isa;
+use crate::DynResult;
+
+/// A single executed instruction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// How many instructions were executed before this one.
+    pub step: usize,
+    pub pc: usize,
+    pub instr: Instr,
+    pub acc_before: i64,
+    pub acc_after: i64,
+}
+
+/// e.g: `#12 pc=4 jmp -3 acc=5->5`
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} pc={} {} acc={}->{}",
+            self.step, self.pc, self.instr, self.acc_before, self.acc_after
+        )
+    }
+}
+
+impl std::str::FromStr for TraceEntry {
+    type Err = Box<dyn std::error::Error + Send + Sync>;
+    fn from_str(s: &str) -> DynResult<TraceEntry> {
+        let s = s.strip_prefix('#').ok_or("expected `#<step>`")?;
+        let (step, s) = s.split_once(" pc=").ok_or("expected `pc=`")?;
+        let (pc, s) = s.split_once(' ').ok_or("missing instruction")?;
+        let (instr, acc) = s.rsplit_once(" acc=").ok_or("expected `acc=`")?;
+        let (acc_before, acc_after) = acc.split_once("->").ok_or("expected `->`")?;
+
+        Ok(TraceEntry {
+            step: step.parse()?,
+            pc: pc.parse()?,
+            instr: Isa::extended().parse(instr)?,
+            acc_before: acc_before.parse()?,
+            acc_after: acc_after.parse()?,
+        })
+    }
+}
+
+/// Where trace entries end up.
+pub enum Trace {
+    /// Keep the most recent `cap` entries in memory.
+    Ring {
+        entries: VecDeque<TraceEntry>,
+        cap: usize,
+    },
+    /// Write every entry out, one per line.
+    Writer(Box<dyn Write + Send>),
+}
+
+impl Trace {
+    pub fn ring(cap: usize) -> Trace {
+        Trace::Ring {
+            entries: VecDeque::with_capacity(cap),
+            cap,
+        }
+    }
+
+    /// Write entries to a (newly created) file.
+    pub fn file(path: &Path) -> io::Result<Trace> {
+        let file = std::fs::File::create(path)?;
+        Ok(Trace::Writer(Box::new(BufWriter::new(file))))
+    }
+
+    pub fn record(&mut self, entry: TraceEntry) -> io::Result<()> {
+        match self {
+            Trace::Ring { entries, cap } => {
+                if entries.len() == *cap {
+                    entries.pop_front();
+                }
+                if *cap > 0 {
+                    entries.push_back(entry);
+                }
+                Ok(())
+            }
+            Trace::Writer(w) => writeln!(w, "{}", entry),
+        }
+    }
+
+    /// Forget any in-memory entries. Does nothing for writers.
+    pub fn clear(&mut self) {
+        if let Trace::Ring { entries, .. } = self {
+            entries.clear();
+        }
+    }
+
+    /// Forget the most recent in-memory entry (e.g: after stepping back).
+    /// Does nothing for writers.
+    pub fn pop(&mut self) -> Option<TraceEntry> {
+        match self {
+            Trace::Ring { entries, .. } => entries.pop_back(),
+            Trace::Writer(_) => None,
+        }
+    }
+
+    /// The in-memory entries (if any), oldest first.
+    pub fn entries(&self) -> Option<&VecDeque<TraceEntry>> {
+        match self {
+            Trace::Ring { entries, .. } => Some(entries),
+            Trace::Writer(_) => None,
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Trace::Ring { .. } => Ok(()),
+            Trace::Writer(w) => w.flush(),
+        }
+    }
+}
+
+/// Parse a trace which was written out by [`Trace::Writer`].
+pub fn parse(input: &str) -> Result<Vec<TraceEntry>, InputError> {
+    input
+        .lines()
+        .filter(|ln| !ln.trim().is_empty())
+        .map(|ln| ln.parse::<TraceEntry>().at(input, ln))
+        .collect()
+}
+
+/// A loop found in a trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// The first pc to be executed a second time.
+    pub pc: usize,
+    /// The instructions making up the loop, starting (and ending just before)
+    /// `pc`.
+    pub entries: Vec<TraceEntry>,
+}
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "pc {} was executed again, after a loop of {} instruction(s):",
+            self.pc,
+            self.entries.len()
+        )?;
+        for e in &self.entries {
+            writeln!(
+                f,
+                "{:>6}: {:<10} acc {} -> {}",
+                e.pc,
+                e.instr.to_string(),
+                e.acc_before,
+                e.acc_after
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the first pc which is executed twice, along with the instructions
+/// executed in between.
+pub fn find_cycle<'a>(entries: impl IntoIterator<Item = &'a TraceEntry>) -> Option<Cycle> {
+    let entries = entries.into_iter().collect::<Vec<_>>();
+    let mut first_seen = HashMap::new();
+    for (i, e) in entries.iter().enumerate() {
+        if let Some(&start) = first_seen.get(&e.pc) {
+            return Some(Cycle {
+                pc: e.pc,
+                entries: entries[start..i].iter().map(|&&e| e).collect(),
+            });
+        }
+        first_seen.insert(e.pc, i);
+    }
+    None
+}
+
+/// Run a program until it loops (or terminates), recording a trace to
+/// `trace`. Unlike [`Vm::run_with_loop_detect`], the first repeated
+/// instruction is executed too, so the trace includes the entire loop.
+///
+/// Returns true if the program terminates.
+pub fn record(asm: &str, trace: Trace) -> DynResult<(bool, Trace)> {
+    let mut vm = Vm::with_isa(asm, &Isa::extended())?.with_trace(trace);
+    let terminated = vm.run_with_loop_detect()?;
+    if !terminated {
+        vm.cycle()?;
+    }
+
+    let mut trace = vm.trace.take().unwrap();
+    trace.flush()?;
+    Ok((terminated, trace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::isa;
+
+    const EXAMPLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    #[test]
+    fn entries_round_trip() {
+        let entry = TraceEntry {
+            step: 12,
+            pc: 4,
+            instr: Instr::Jmp(-3),
+            acc_before: -5,
+            acc_after: -5,
+        };
+        assert_eq!(entry.to_string(), "#12 pc=4 jmp -3 acc=-5->-5");
+        assert_eq!(entry.to_string().parse::<TraceEntry>().unwrap(), entry);
+
+        assert!(parse("#1 pc=2 acc +1 acc=0->1\n#2 pc=x nop +0 acc=1->1").is_err());
+    }
+
+    #[test]
+    fn extensions_round_trip() {
+        let entry = TraceEntry {
+            step: 3,
+            pc: 1,
+            instr: Instr::Ext(&isa::SET, [2, -7]),
+            acc_before: 4,
+            acc_after: 4,
+        };
+        assert_eq!(entry.to_string(), "#3 pc=1 set r2 -7 acc=4->4");
+        assert_eq!(entry.to_string().parse::<TraceEntry>().unwrap(), entry);
+
+        // programs using extensions can be recorded, and read back
+        let (terminated, trace) = record("acc +2\nout +1\njmp -1", Trace::ring(100)).unwrap();
+        assert!(!terminated);
+        let entries = trace.entries().unwrap();
+        assert_eq!(entries[1].instr, Instr::Ext(&isa::OUT, [1, 0]));
+        let text = entries
+            .iter()
+            .map(|e| e.to_string() + "\n")
+            .collect::<String>();
+        assert_eq!(
+            parse(&text).unwrap(),
+            entries.iter().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn ring() {
+        let (terminated, trace) = record(EXAMPLE, Trace::ring(4)).unwrap();
+        assert!(!terminated);
+
+        let entries = trace.entries().unwrap();
+        assert_eq!(
+            entries.iter().map(|e| e.step).collect::<Vec<_>>(),
+            [4, 5, 6, 7]
+        );
+        assert_eq!(entries.back().unwrap().pc, 1);
+        assert_eq!(find_cycle(entries), None);
+    }
+
+    #[test]
+    fn cycle() {
+        let (_, trace) = record(EXAMPLE, Trace::ring(100)).unwrap();
+        let cycle = find_cycle(trace.entries().unwrap()).unwrap();
+        assert_eq!(cycle.pc, 1);
+        assert_eq!(
+            cycle.entries.iter().map(|e| e.pc).collect::<Vec<_>>(),
+            [1, 2, 6, 7, 3, 4]
+        );
+        assert_eq!(cycle.entries[0].acc_before, 0);
+        assert_eq!(cycle.entries.last().unwrap().acc_after, 5);
+
+        // replaying a written trace finds the same loop
+        let text = trace
+            .entries()
+            .unwrap()
+            .iter()
+            .map(|e| e.to_string() + "\n")
+            .collect::<String>();
+        assert_eq!(find_cycle(&parse(&text).unwrap()), Some(cycle));
+
+        let fixed = EXAMPLE.replace("jmp -4", "nop -4");
+        let (terminated, trace) = record(&fixed, Trace::ring(100)).unwrap();
+        assert!(terminated);
+        assert_eq!(find_cycle(trace.entries().unwrap()), None);
+    }
+}
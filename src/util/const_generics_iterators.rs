@@ -1,4 +1,11 @@
 /// Constructed through the `SliceExt::combinations()` method.
+///
+/// ```
+/// use aoc20::util::ext_traits::SliceExt;
+///
+/// let pairs = [1, 2, 3].combinations::<2>().collect::<Vec<_>>();
+/// assert_eq!(pairs, [[&1, &2], [&1, &3], [&2, &3]]);
+/// ```
 pub struct Combinations<'a, T, const N: usize> {
     arr: &'a [T],
     idx: [usize; N],
@@ -77,6 +84,14 @@ mod combinations_tests {
 }
 
 /// Constructed through the `ArrayExt::cartesian_product()` method.
+///
+/// ```
+/// use aoc20::util::const_generics_iterators::CartesianProduct;
+///
+/// let product = CartesianProduct::new([0..2, 0..2]).collect::<Vec<_>>();
+/// assert_eq!(product.len(), 4);
+/// assert!(product.contains(&[1, 0]));
+/// ```
 #[derive(Clone)]
 pub struct CartesianProduct<I, const N: usize>
 where
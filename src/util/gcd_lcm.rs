@@ -1,3 +1,12 @@
+/// GCD and LCM for unsigned integers.
+///
+/// ```
+/// use aoc20::util::gcd_lcm::GcdLcm;
+///
+/// assert_eq!(12u64.gcd(18), 6);
+/// assert_eq!(4usize.lcm(6), 12);
+/// assert_eq!(u32::lcm_list(vec![2, 3, 4]), 12);
+/// ```
 pub trait GcdLcm: Sized + num_traits::PrimInt {
     /// Find the Greatest Common Divisor between two numbers
     fn gcd(self, other: Self) -> Self {
@@ -0,0 +1,391 @@
+//! A dense, fixed-size 2D grid, for all those questions with ASCII-art inputs.
+//!
+//! Positions are `(x, y)` pairs, where `x` is the column and `y` is the row,
+//! and `(0, 0)` is the top-left corner.
+
+use core::fmt::{self, Display};
+use core::ops::{Index, IndexMut};
+
+use crate::error::InputError;
+
+/// Offsets to the 4 orthogonally adjacent cells (N, E, S, W).
+pub const DIRS4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Offsets to all 8 adjacent cells, clockwise from N.
+pub const DIRS8: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// A `width` x `height` grid of cells, stored in row-major order.
+///
+/// ```
+/// use aoc20::util::grid::Grid;
+///
+/// let grid = Grid::parse("#..\n.#.", |c| match c {
+///     '#' => Ok(true),
+///     '.' => Ok(false),
+///     _ => Err("expected '.' or '#'"),
+/// })
+/// .unwrap();
+///
+/// assert_eq!((grid.width(), grid.height()), (3, 2));
+/// assert!(grid[(1, 1)]);
+/// assert_eq!(grid.get(3, 0), None);
+/// assert_eq!(grid.get_wrapping(4, -1), &true);
+/// assert_eq!(grid.neighbors8((0, 0)).filter(|&p| grid[p]).count(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Create a grid with every cell set to `fill`.
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Create a grid from its rows. Returns `None` if the rows aren't all the
+    /// same length.
+    pub fn from_rows(rows: impl IntoIterator<Item = Vec<T>>) -> Option<Grid<T>> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for row in rows {
+            if *width.get_or_insert(row.len()) != row.len() {
+                return None;
+            }
+            cells.extend(row);
+            height += 1;
+        }
+
+        Some(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    /// Parse a grid from text, with one row per line, converting each char
+    /// using `cell`.
+    ///
+    /// Errors point at the offending char, or at the offending row if the
+    /// rows aren't all the same (non-zero) length.
+    pub fn parse<E: Display>(
+        input: &str,
+        mut cell: impl FnMut(char) -> Result<T, E>,
+    ) -> Result<Grid<T>, InputError> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for ln in input.split('\n') {
+            let expected = *width.get_or_insert(ln.chars().count());
+            if expected == 0 {
+                return Err(InputError::new(input, ln, "empty row"));
+            }
+            if ln.chars().count() != expected {
+                return Err(InputError::new(
+                    input,
+                    ln,
+                    format!("expected row to be {} wide", expected),
+                ));
+            }
+            for (i, c) in ln.char_indices() {
+                cells.push(cell(c).map_err(|e| InputError::new(input, &ln[i..], e))?);
+            }
+            height += 1;
+        }
+
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether `(x, y)` lies within the grid.
+    pub fn in_bounds(&self, x: isize, y: isize) -> bool {
+        (0..self.width as isize).contains(&x) && (0..self.height as isize).contains(&y)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width && y < self.height {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Get a cell, treating the grid as though it repeats infinitely in every
+    /// direction. Panics if the grid is empty.
+    pub fn get_wrapping(&self, x: isize, y: isize) -> &T {
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        &self[(x, y)]
+    }
+
+    /// Offset a position by `(dx, dy)`, returning `None` if that lands outside
+    /// the grid.
+    pub fn offset(
+        &self,
+        (x, y): (usize, usize),
+        (dx, dy): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let (x, y) = (x as isize + dx, y as isize + dy);
+        if self.in_bounds(x, y) {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Positions of the (up to) 4 orthogonally adjacent cells.
+    pub fn neighbors4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRS4.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+
+    /// Positions of the (up to) 8 adjacent cells, including diagonals.
+    pub fn neighbors8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRS8.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+
+    /// Positions along a ray cast from `pos` (exclusive) in the direction
+    /// `(dx, dy)`, up to the edge of the grid.
+    ///
+    /// A zero direction yields nothing, rather than repeating `pos` forever.
+    pub fn ray(
+        &self,
+        pos: (usize, usize),
+        dir: (isize, isize),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut pos = if dir == (0, 0) { None } else { Some(pos) };
+        core::iter::from_fn(move || {
+            pos = self.offset(pos?, dir);
+            pos
+        })
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        assert!(x < self.width, "column out of bounds");
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    /// Every cell, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.cells.iter()
+    }
+
+    /// Every position, in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell along with its position, in row-major order.
+    pub fn enumerate(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Flip the grid over its main diagonal (i.e: rows become columns).
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: (0..self.width)
+                .flat_map(|x| self.column(x).cloned())
+                .collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is out of bounds",
+            x,
+            y
+        );
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is out of bounds",
+            x,
+            y
+        );
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+/// Renders each row on its own line, with cells printed back-to-back.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y != 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Grid<char> {
+        Grid::parse(s, Ok::<_, &str>).unwrap()
+    }
+
+    #[test]
+    fn parse_errors() {
+        let e = Grid::parse("ab\nc", Ok::<_, &str>).unwrap_err();
+        assert_eq!((e.line, e.msg.as_str()), (2, "expected row to be 2 wide"));
+
+        let e = Grid::parse("\n", Ok::<_, &str>).unwrap_err();
+        assert_eq!(e.msg, "empty row");
+
+        let e = Grid::parse("..\n.x", |c| match c {
+            '.' => Ok(()),
+            _ => Err("expected '.'"),
+        })
+        .unwrap_err();
+        assert_eq!((e.line, e.col), (2, 2));
+    }
+
+    #[test]
+    fn access() {
+        let mut grid = chars("abc\ndef");
+        assert_eq!(grid[(2, 1)], 'f');
+        assert_eq!(grid.get(0, 2), None);
+        assert_eq!(*grid.get_wrapping(-1, 3), 'f');
+        assert_eq!(*grid.get_wrapping(3, -2), 'a');
+
+        grid[(0, 0)] = 'z';
+        *grid.get_mut(1, 0).unwrap() = 'y';
+        assert_eq!(grid.row(0), ['z', 'y', 'c']);
+        assert!(grid.get_mut(3, 0).is_none());
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = chars("abc\ndef\nghi");
+        let at = |ps: Vec<(usize, usize)>| ps.into_iter().map(|p| grid[p]).collect::<String>();
+
+        assert_eq!(at(grid.neighbors4((1, 1)).collect()), "bfhd");
+        assert_eq!(at(grid.neighbors8((1, 1)).collect()), "bcfihgda");
+        assert_eq!(at(grid.neighbors4((0, 0)).collect()), "bd");
+        assert_eq!(at(grid.neighbors8((2, 2)).collect()), "fhe");
+    }
+
+    #[test]
+    fn rays() {
+        let grid = chars("abcd\nefgh\nijkl");
+        let ray = |pos, dir| grid.ray(pos, dir).map(|p| grid[p]).collect::<String>();
+
+        assert_eq!(ray((0, 0), (1, 0)), "bcd");
+        assert_eq!(ray((1, 2), (0, -1)), "fb");
+        assert_eq!(ray((0, 0), (1, 1)), "fk");
+        assert_eq!(ray((3, 2), (-1, -1)), "gb");
+        assert_eq!(ray((3, 0), (1, 0)), "");
+        assert_eq!(ray((1, 1), (0, 0)), "");
+
+        assert_eq!(grid.column(1).collect::<String>(), "bfj");
+        assert_eq!(grid.rows().count(), 3);
+    }
+
+    #[test]
+    fn transpose_and_display() {
+        let grid = chars("abc\ndef");
+        let t = grid.transpose();
+        assert_eq!((t.width(), t.height()), (2, 3));
+        assert_eq!(t.to_string(), "ad\nbe\ncf");
+        assert_eq!(t.transpose(), grid);
+
+        assert_eq!(grid.map(|c| c.to_ascii_uppercase()).to_string(), "ABC\nDEF");
+        assert_eq!(
+            Grid::from_rows(vec![vec![1, 2], vec![3, 4]])
+                .unwrap()
+                .to_string(),
+            "12\n34"
+        );
+        assert!(Grid::from_rows(vec![vec![1, 2], vec![3]]).is_none());
+        assert_eq!(Grid::new(2, 1, 0).to_string(), "00");
+    }
+
+    #[test]
+    fn enumerate() {
+        let grid = chars("ab\ncd");
+        assert_eq!(
+            grid.enumerate().collect::<Vec<_>>(),
+            [
+                ((0, 0), &'a'),
+                ((1, 0), &'b'),
+                ((0, 1), &'c'),
+                ((1, 1), &'d')
+            ]
+        );
+        assert_eq!(grid.iter().filter(|&&c| c > 'b').count(), 2);
+    }
+}
@@ -1,3 +1,7 @@
 pub mod const_generics_iterators;
 pub mod ext_traits;
 pub mod gcd_lcm;
+pub mod grid;
+pub mod point;
+pub mod prop;
+pub mod rng;
@@ -0,0 +1,251 @@
+//! Points (and vectors) in N-dimensional integer space.
+//!
+//! There's no separate vector type: a [`Vector`] is just a [`Point`] which
+//! happens to be used as an offset, which keeps the arithmetic simple.
+
+use core::ops::{Add, AddAssign, Mul, Neg, RangeInclusive, Sub, SubAssign};
+
+use crate::util::ext_traits::ArrayExt;
+
+/// A point in `N` dimensions.
+///
+/// ```
+/// use aoc20::util::point::Point;
+///
+/// let p = Point([1, 2]);
+/// assert_eq!(p + Point([3, -4]) * 2, Point([7, -6]));
+/// assert_eq!(p.manhattan_dist(Point([-1, 0])), 4);
+/// assert_eq!(p.chebyshev_dist(Point([-1, 0])), 2);
+/// assert_eq!(p.rotate_cw(), Point([2, -1]));
+/// assert_eq!(Point([0, 0, 0]).neighbors().count(), 26);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point<const N: usize>(pub [isize; N]);
+
+/// A displacement between two [`Point`]s.
+pub type Vector<const N: usize> = Point<N>;
+
+impl<const N: usize> Point<N> {
+    pub const ORIGIN: Point<N> = Point([0; N]);
+
+    /// Combine the coordinates of two points pairwise.
+    fn zip_with(self, other: Self, mut f: impl FnMut(isize, isize) -> isize) -> Self {
+        let mut out = self;
+        for (a, b) in out.0.iter_mut().zip(other.0.iter()) {
+            *a = f(*a, *b);
+        }
+        out
+    }
+
+    /// Like [`Point::zip_with`], but bails if `f` returns `None`.
+    fn try_zip_with(
+        self,
+        other: Self,
+        mut f: impl FnMut(isize, isize) -> Option<isize>,
+    ) -> Option<Self> {
+        let mut out = self;
+        for (a, b) in out.0.iter_mut().zip(other.0.iter()) {
+            *a = f(*a, *b)?;
+        }
+        Some(out)
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.try_zip_with(other, isize::checked_add)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.try_zip_with(other, isize::checked_sub)
+    }
+
+    pub fn checked_mul(self, k: isize) -> Option<Self> {
+        self.try_zip_with(self, |a, _| a.checked_mul(k))
+    }
+
+    /// Distance from the origin, moving along one axis at a time. Returns
+    /// `None` on overflow.
+    pub fn checked_manhattan(self) -> Option<usize> {
+        (self.0.iter()).try_fold(0usize, |acc, c| acc.checked_add(c.checked_abs()? as usize))
+    }
+
+    /// Distance between two points, moving along one axis at a time.
+    pub fn manhattan_dist(self, other: Self) -> usize {
+        (self - other).0.iter().map(|c| c.unsigned_abs()).sum()
+    }
+
+    /// Distance between two points, moving along any number of axes at once
+    /// (i.e: like a king in chess).
+    pub fn chebyshev_dist(self, other: Self) -> usize {
+        (self - other)
+            .0
+            .iter()
+            .map(|c| c.unsigned_abs())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The `3^N - 1` points adjacent to this one, including diagonals.
+    pub fn neighbors(self) -> impl Iterator<Item = Point<N>> {
+        const ADJ: RangeInclusive<isize> = -1..=1;
+        [ADJ; N]
+            .cartesian_product()
+            .filter(|d| *d != [0; N])
+            .map(move |d| self + Point(d))
+    }
+
+    /// The `2 * N` points adjacent to this one along a single axis.
+    pub fn orthogonal_neighbors(self) -> impl Iterator<Item = Point<N>> {
+        (0..N).flat_map(move |axis| {
+            [-1, 1].iter().map(move |&d| {
+                let mut p = self;
+                p.0[axis] += d;
+                p
+            })
+        })
+    }
+}
+
+/// 2D specific operations. Rotations treat `+y` as "up" (i.e: north), so
+/// clockwise takes `+y` to `+x`.
+impl Point<2> {
+    pub fn x(self) -> isize {
+        self.0[0]
+    }
+
+    pub fn y(self) -> isize {
+        self.0[1]
+    }
+
+    /// Rotate 90° clockwise about the origin.
+    pub fn rotate_cw(self) -> Self {
+        Point([self.y(), -self.x()])
+    }
+
+    /// Rotate 90° counter-clockwise about the origin.
+    pub fn rotate_ccw(self) -> Self {
+        Point([-self.y(), self.x()])
+    }
+
+    /// Rotate clockwise about the origin by `quarter_turns` * 90°. Negative
+    /// turns go counter-clockwise.
+    pub fn rotate(self, quarter_turns: isize) -> Self {
+        match quarter_turns.rem_euclid(4) {
+            0 => self,
+            1 => self.rotate_cw(),
+            2 => -self,
+            _ => self.rotate_ccw(),
+        }
+    }
+}
+
+impl<const N: usize> From<[isize; N]> for Point<N> {
+    fn from(coords: [isize; N]) -> Self {
+        Point(coords)
+    }
+}
+
+impl From<(isize, isize)> for Point<2> {
+    fn from((x, y): (isize, isize)) -> Self {
+        Point([x, y])
+    }
+}
+
+impl<const N: usize> Add for Point<N> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a + b)
+    }
+}
+
+impl<const N: usize> Sub for Point<N> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a - b)
+    }
+}
+
+impl<const N: usize> AddAssign for Point<N> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other
+    }
+}
+
+impl<const N: usize> SubAssign for Point<N> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other
+    }
+}
+
+impl<const N: usize> Neg for Point<N> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Point::ORIGIN - self
+    }
+}
+
+impl<const N: usize> Mul<isize> for Point<N> {
+    type Output = Self;
+    fn mul(self, k: isize) -> Self {
+        self.zip_with(self, |a, _| a * k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    #[test]
+    fn arithmetic() {
+        let mut p = Point([1, -2, 3]);
+        p += Point([1, 1, 1]);
+        assert_eq!(p, Point([2, -1, 4]));
+        p -= Point([2, 2, 2]);
+        assert_eq!(-p, Point([0, 3, -2]));
+        assert_eq!(p * -1, -p);
+
+        assert_eq!(Point([isize::MAX]).checked_add(Point([1])), None);
+        assert_eq!(Point([isize::MIN]).checked_sub(Point([1])), None);
+        assert_eq!(Point([2, 3]).checked_mul(isize::MAX), None);
+        assert_eq!(Point([2, 3]).checked_mul(2), Some(Point([4, 6])));
+    }
+
+    #[test]
+    fn distances() {
+        let (a, b) = (Point([1, 5, -3]), Point([-2, 4, 1]));
+        assert_eq!(a.manhattan_dist(b), 8);
+        assert_eq!(a.chebyshev_dist(b), 4);
+        assert_eq!((a - b).checked_manhattan(), Some(8));
+        assert_eq!(Point([isize::MIN, 0]).checked_manhattan(), None);
+        assert_eq!(
+            Point([isize::MAX, 1]).checked_manhattan(),
+            Some(isize::MAX as usize + 1)
+        );
+        assert_eq!(Point([isize::MAX, isize::MAX, 2]).checked_manhattan(), None);
+    }
+
+    #[test]
+    fn neighbors() {
+        let p = Point([5, 5, 5, 5]);
+        let all = p.neighbors().collect::<HashSet<_>>();
+        assert_eq!(all.len(), 80);
+        assert!(all.iter().all(|&n| n.chebyshev_dist(p) == 1));
+
+        let orth = p.orthogonal_neighbors().collect::<HashSet<_>>();
+        assert_eq!(orth.len(), 8);
+        assert!(orth.iter().all(|&n| n.manhattan_dist(p) == 1));
+        assert!(orth.is_subset(&all));
+    }
+
+    #[test]
+    fn rotations() {
+        let p = Point::from((10, 4));
+        assert_eq!(p.rotate_cw(), Point([4, -10]));
+        assert_eq!(p.rotate_ccw(), Point([-4, 10]));
+        assert_eq!(p.rotate(2), -p);
+        assert_eq!(p.rotate(-1), p.rotate(3));
+        assert_eq!(p.rotate(4), p);
+        assert_eq!(p.rotate_cw().rotate_ccw(), p);
+    }
+}
@@ -0,0 +1,62 @@
+//! A (very) small property testing harness, for cross-checking solutions
+//! against slow-but-obviously-correct reference implementations.
+//!
+//! Every case is generated from its own seed, so failures are reproducible:
+//! re-run the failing test with `PROP_SEED=<seed>` to check just that case, or
+//! with `PROP_CASES=<n>` to check more cases than the default.
+
+use core::fmt::Debug;
+
+use crate::util::rng::Rng;
+use crate::DynResult;
+
+/// Number of cases checked when `PROP_CASES` isn't set.
+pub const DEFAULT_CASES: u64 = 64;
+
+/// Check that `actual` agrees with `reference` on generated inputs.
+///
+/// `gen` is passed a seeded RNG and a size, which cycles between 1 and
+/// `max_size` (so the first few cases are always small).
+///
+/// ```
+/// use aoc20::prelude::*;
+/// use aoc20::util::prop;
+///
+/// prop::check(
+///     10,
+///     |rng, size| (0..size).map(|_| rng.range(0..100)).join(","),
+///     |input| Ok(input.split(',').map(|n| n.parse::<usize>()).sum::<Result<usize, _>>()?),
+///     |input| input.split(',').fold(0, |sum, n| sum + n.parse::<usize>().unwrap()),
+/// );
+/// ```
+pub fn check<T: PartialEq + Debug>(
+    max_size: usize,
+    gen: impl Fn(&mut Rng, usize) -> String,
+    actual: impl Fn(&str) -> DynResult<T>,
+    reference: impl Fn(&str) -> T,
+) {
+    let seeds = match env_u64("PROP_SEED") {
+        Some(seed) => seed..seed + 1,
+        None => 0..env_u64("PROP_CASES").unwrap_or(DEFAULT_CASES),
+    };
+
+    for seed in seeds {
+        let size = 1 + seed as usize % max_size.max(1);
+        let input = gen(&mut Rng::new(seed), size);
+
+        let expected = reference(&input);
+        let actual = actual(&input);
+        if actual.as_ref().ok() != Some(&expected) {
+            panic!(
+                "property failed (re-run with PROP_SEED={})\n\
+                 expected: {:?}\n  actual: {:?}\n\
+                 input (size {}):\n{}",
+                seed, expected, actual, size, input
+            );
+        }
+    }
+}
+
+fn env_u64(name: &str) -> Option<u64> {
+    std::env::var(name).ok()?.parse().ok()
+}
@@ -0,0 +1,97 @@
+//! A small, seedable PRNG for generating synthetic inputs.
+//!
+//! Not suitable for anything security related (obviously), but it's fast,
+//! dependency-free, and produces the same sequence on every platform.
+
+use core::ops::Range;
+
+/// A `splitmix64` based pseudo-random number generator.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`. Panics if the range is empty.
+    pub fn range(&mut self, range: Range<usize>) -> usize {
+        assert!(!range.is_empty(), "cannot sample from an empty range");
+        let len = (range.end - range.start) as u64;
+        range.start + (self.next_u64() % len) as usize
+    }
+
+    /// A number in `range`. Panics if the range is empty.
+    pub fn range_i64(&mut self, range: Range<i64>) -> i64 {
+        assert!(!range.is_empty(), "cannot sample from an empty range");
+        let len = range.end.wrapping_sub(range.start) as u64;
+        range.start.wrapping_add((self.next_u64() % len) as i64)
+    }
+
+    /// Returns true with probability `num / den`.
+    pub fn chance(&mut self, num: u64, den: u64) -> bool {
+        self.next_u64() % den < num
+    }
+
+    /// A random element of a (non-empty) slice.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..items.len())]
+    }
+
+    /// Shuffle a slice in-place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range(0..i + 1));
+        }
+    }
+
+    /// A random lowercase "word" with a length in `len`.
+    pub fn word(&mut self, len: Range<usize>) -> String {
+        (0..self.range(len))
+            .map(|_| (b'a' + self.range(0..26) as u8) as char)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let a = (0..8).map({
+            let mut rng = Rng::new(42);
+            move |_| rng.next_u64()
+        });
+        let b = (0..8).map({
+            let mut rng = Rng::new(42);
+            move |_| rng.next_u64()
+        });
+        assert!(a.eq(b));
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = Rng::new(0);
+        for _ in 0..1000 {
+            assert!((3..7).contains(&rng.range(3..7)));
+            assert!((-5..5).contains(&rng.range_i64(-5..5)));
+        }
+
+        let mut v = (0..10).collect::<Vec<_>>();
+        rng.shuffle(&mut v);
+        v.sort_unstable();
+        assert_eq!(v, (0..10).collect::<Vec<_>>());
+    }
+}
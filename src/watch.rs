@@ -0,0 +1,139 @@
+//! Re-running a solution whenever its input changes.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::fetch::Fetcher;
+use crate::input::Source;
+use crate::options::Options;
+use crate::runner::{self, fmt_duration};
+use crate::solution::{Answer, Solution};
+use crate::DynResult;
+
+/// Default delay between checking inputs for changes.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// An input being watched for changes.
+struct Watched {
+    source: Source,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    prev: Option<Answer>,
+}
+
+/// Poll the day's input (and optionally, an alternate input) for changes,
+/// re-running the solution against whichever input changed, and printing how
+/// the answer changed since the previous run.
+///
+/// Runs until the process is killed.
+pub fn watch(
+    sol: &dyn Solution,
+    part: usize,
+    alt_input: Option<PathBuf>,
+    fetcher: &Fetcher,
+    opts: &Options,
+    interval: Duration,
+) -> DynResult<()> {
+    let mut watched = vec![Watched {
+        source: Source::Default,
+        path: fetcher.input_path(sol.year(), sol.day()),
+        modified: None,
+        prev: None,
+    }];
+    if let Some(path) = alt_input {
+        watched.push(Watched {
+            source: Source::Path(path.clone()),
+            path,
+            modified: None,
+            prev: None,
+        });
+    }
+
+    println!(
+        "watching {} (ctrl-c to stop)",
+        watched
+            .iter()
+            .map(|w| w.path.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let mut first = true;
+    loop {
+        for w in watched.iter_mut() {
+            let modified = std::fs::metadata(&w.path).and_then(|m| m.modified()).ok();
+            // always run on the first pass, even if the default input doesn't
+            // exist yet (in which case loading it downloads it)
+            if !first && modified == w.modified {
+                continue;
+            }
+            w.modified = modified;
+
+            let label = w.path.to_string_lossy();
+            let input = match w.source.load(sol, fetcher) {
+                Ok(input) => input,
+                Err(e) => {
+                    eprintln!("[{}] could not load input: {}", label, e);
+                    continue;
+                }
+            };
+
+            let run = runner::run(sol, part, &input, opts);
+            match run.answer {
+                Ok(answer) => {
+                    println!(
+                        "[{}] Answer: {} ({}, parse {}, solve {})",
+                        label,
+                        answer,
+                        describe_change(w.prev.as_ref(), &answer),
+                        fmt_duration(run.parse),
+                        fmt_duration(run.solve),
+                    );
+                    w.prev = Some(answer);
+                }
+                Err(e) => eprintln!("[{}] Error: {}", label, e),
+            }
+
+            // the default input might have just been downloaded
+            w.modified = std::fs::metadata(&w.path).and_then(|m| m.modified()).ok();
+        }
+
+        first = false;
+        std::thread::sleep(interval);
+    }
+}
+
+/// Describe how an answer changed from the previous run.
+fn describe_change(prev: Option<&Answer>, cur: &Answer) -> String {
+    match prev {
+        None => "first run".to_string(),
+        Some(prev) if prev == cur => "unchanged".to_string(),
+        Some(Answer::Int(prev)) => match cur {
+            Answer::Int(cur) => format!("was {}, {:+}", prev, cur - prev),
+            Answer::Str(_) => format!("was {}", prev),
+        },
+        Some(prev) => format!("was {}", prev),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes() {
+        assert_eq!(describe_change(None, &Answer::Int(5)), "first run");
+        assert_eq!(
+            describe_change(Some(&Answer::Int(5)), &Answer::Int(5)),
+            "unchanged"
+        );
+        assert_eq!(
+            describe_change(Some(&Answer::Int(5)), &Answer::Int(3)),
+            "was 5, -2"
+        );
+        assert_eq!(
+            describe_change(Some(&"abc".into()), &"abd".into()),
+            "was abc"
+        );
+    }
+}